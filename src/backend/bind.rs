@@ -82,7 +82,7 @@ pub struct BoundGlobal {
 impl BoundGlobal {
     pub fn bind_to<P: AsRef<DictRef>>(
         registry: &pw::registry::Registry,
-        global: &GlobalObject<&P>,
+        global: &GlobalObject<P>,
         on_event: impl Fn(Event) + Clone + 'static,
         proxy_removed: impl Fn() + 'static,
     ) -> Result<Self, Error> {
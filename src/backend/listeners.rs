@@ -220,12 +220,24 @@ pub fn link(link: pw::link::Link, id: u32, on_event: impl Fn(Event) + 'static) -
                     pw::link::LinkState::Error(e) => e,
                 }
                 .to_owned();
+                let format = info.format().map_or_else(
+                    || "None".to_owned(),
+                    |pod| match pw::spa::param::format_utils::parse_format(pod) {
+                        Ok((media_type, media_subtype)) => format!(
+                            "{}/{}",
+                            format!("{media_type:?}").trim_start_matches("MediaType::"),
+                            format!("{media_subtype:?}").trim_start_matches("MediaSubtype::")
+                        ),
+                        Err(_) => "Unknown".to_owned(),
+                    },
+                );
                 let infos = Box::new([
                     ("Input Node ID", info.input_node_id().to_string()),
                     ("Input Port ID", info.input_port_id().to_string()),
                     ("Output Node ID", info.output_node_id().to_string()),
                     ("Output Port ID", info.output_port_id().to_string()),
                     ("State", state),
+                    ("Format", format),
                 ]);
 
                 let props = info
@@ -0,0 +1,201 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Capture streams that measure the levels of nodes
+
+#![allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+
+use super::{
+    Event,
+    pw::{
+        self,
+        spa::{
+            self,
+            param::{
+                ParamType,
+                audio::{AudioFormat, AudioInfoRaw},
+                format::{MediaSubtype, MediaType},
+                format_utils,
+            },
+            pod::{Pod, Value, serialize::PodSerializer},
+        },
+    },
+    util,
+};
+
+/// Prefix of the names of the meter streams
+pub const NAME_PREFIX: &str = "coppwr.meter.";
+
+/// Levels are sent this many times per second
+const UPDATES_PER_SECOND: u32 = 30;
+
+/// Linear levels of a channel since the previous update
+#[derive(Clone, Copy, Default)]
+pub struct Level {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+struct State {
+    format: AudioInfoRaw,
+    peaks: Vec<f32>,
+    squares: Vec<f32>,
+    frames: u32,
+}
+
+#[allow(dead_code)] // The fields are never read from, they are kept to keep the stream alive
+pub struct Meter {
+    listener: pw::stream::StreamListener<State>,
+    stream: pw::stream::StreamRc,
+}
+
+impl Meter {
+    /// Connects a stream to the node with the properties that target it
+    pub fn new(
+        core: &pw::core::CoreRc,
+        node: u32,
+        props: Vec<(String, String)>,
+        send: impl Fn(Event) + 'static,
+    ) -> Result<Self, pw::Error> {
+        let mut props = util::key_val_to_props(props.into_iter());
+        props.insert(*pw::keys::MEDIA_TYPE, "Audio");
+        props.insert(*pw::keys::MEDIA_CATEGORY, "Capture");
+        props.insert(*pw::keys::MEDIA_ROLE, "DSP");
+        props.insert(*pw::keys::NODE_NAME, format!("{NAME_PREFIX}{node}"));
+        // Metering should not keep the node running or follow it elsewhere
+        props.insert(*pw::keys::NODE_PASSIVE, "true");
+        props.insert(*pw::keys::NODE_DONT_RECONNECT, "true");
+        // Keep the channels of the node
+        props.insert("stream.dont-remix", "true");
+
+        let stream = pw::stream::StreamRc::new(core.clone(), "coppwr level meter", props)?;
+
+        let listener = stream
+            .add_local_listener_with_user_data(State {
+                format: AudioInfoRaw::new(),
+                peaks: Vec::new(),
+                squares: Vec::new(),
+                frames: 0,
+            })
+            .param_changed(|_, state, id, param| {
+                let Some(param) = param else {
+                    return;
+                };
+
+                if id != ParamType::Format.as_raw() {
+                    return;
+                }
+
+                let Ok((media_type, media_subtype)) = format_utils::parse_format(param) else {
+                    return;
+                };
+
+                if media_type != MediaType::Audio || media_subtype != MediaSubtype::Raw {
+                    return;
+                }
+
+                if state.format.parse(param).is_ok() {
+                    let channels = state.format.channels() as usize;
+                    state.peaks = vec![0.; channels];
+                    state.squares = vec![0.; channels];
+                    state.frames = 0;
+                }
+            })
+            .process(move |stream, state| {
+                let Some(mut buffer) = stream.dequeue_buffer() else {
+                    return;
+                };
+
+                let channels = state.peaks.len();
+                let Some(data) = buffer.datas_mut().first_mut().filter(|_| channels > 0) else {
+                    return;
+                };
+
+                let offset = data.chunk().offset() as usize;
+                let size = data.chunk().size() as usize;
+
+                let Some(samples) = data
+                    .data()
+                    .and_then(|bytes| bytes.get(offset..offset + size))
+                else {
+                    return;
+                };
+
+                // Interleaved F32 samples
+                for (i, sample) in samples.chunks_exact(4).enumerate() {
+                    let Ok(sample) = <[u8; 4]>::try_from(sample) else {
+                        continue;
+                    };
+                    let sample = f32::from_le_bytes(sample);
+
+                    let channel = i % channels;
+                    state.peaks[channel] = state.peaks[channel].max(sample.abs());
+                    state.squares[channel] += sample * sample;
+                }
+
+                state.frames += (size / 4 / channels) as u32;
+
+                if state.frames < state.format.rate() / UPDATES_PER_SECOND {
+                    return;
+                }
+
+                let frames = state.frames as f32;
+                let levels = state
+                    .peaks
+                    .iter()
+                    .zip(&state.squares)
+                    .map(|(&peak, &square)| Level {
+                        peak,
+                        rms: (square / frames).sqrt(),
+                    })
+                    .collect();
+
+                send(Event::Levels { node, levels });
+
+                state.peaks.fill(0.);
+                state.squares.fill(0.);
+                state.frames = 0;
+            })
+            .register()?;
+
+        // Leave the rate and channels empty to get the ones of the node
+        let mut format = AudioInfoRaw::new();
+        format.set_format(AudioFormat::F32LE);
+
+        let values: Vec<u8> = PodSerializer::serialize(
+            std::io::Cursor::new(Vec::new()),
+            &Value::Object(spa::pod::Object {
+                type_: spa::utils::SpaTypes::ObjectParamFormat.as_raw(),
+                id: ParamType::EnumFormat.as_raw(),
+                properties: format.into(),
+            }),
+        )
+        .map_err(|_| pw::Error::CreationFailed)?
+        .0
+        .into_inner();
+
+        let mut params = [Pod::from_bytes(&values).ok_or(pw::Error::CreationFailed)?];
+
+        stream.connect(
+            spa::utils::Direction::Input,
+            None,
+            pw::stream::StreamFlags::AUTOCONNECT | pw::stream::StreamFlags::MAP_BUFFERS,
+            &mut params,
+        )?;
+
+        Ok(Self { listener, stream })
+    }
+}
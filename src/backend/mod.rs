@@ -16,6 +16,7 @@
 
 mod bind;
 mod connection;
+mod meter;
 mod pipewire;
 pub mod pods;
 mod util;
@@ -23,6 +24,7 @@ mod util;
 use ::pipewire as pw;
 
 use connection::Connection;
+pub use meter::{Level, NAME_PREFIX as METER_NAME_PREFIX};
 
 pub type Sender = pw::channel::Sender<Request>;
 
@@ -54,6 +56,15 @@ pub enum Request {
     GetContextProperties,
     UpdateContextProperties(std::collections::BTreeMap<String, String>),
     CallObjectMethod(u32, ObjectMethod),
+    /// Bind only this Profiler global, or none to stop profiling
+    SelectProfiler(Option<u32>),
+    /// Capture from the node to measure its levels.
+    /// The properties are added to the capture stream to target the node.
+    StartMeter {
+        node: u32,
+        props: Vec<(String, String)>,
+    },
+    StopMeter(u32),
 }
 
 pub enum Event {
@@ -87,6 +98,11 @@ pub enum Event {
         media_type: pw::spa::param::format::MediaType,
     },
     ContextProperties(std::collections::BTreeMap<String, String>),
+    /// Levels of each channel of a metered node
+    Levels {
+        node: u32,
+        levels: Vec<Level>,
+    },
     Stop,
 }
 
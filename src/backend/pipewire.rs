@@ -14,13 +14,19 @@
 //
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::mpsc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    sync::mpsc,
+};
 
 use crate::backend::connection;
 
 use super::{
     Connection, Event, RemoteInfo, Request,
     bind::BoundGlobal,
+    meter::Meter,
     pw::{self, proxy::ProxyT, types::ObjectType},
     util,
 };
@@ -51,6 +57,17 @@ pub fn pipewire_thread(
     let locals = Rc::new(RefCell::new(HashMap::new()));
     let binds = Rc::new(RefCell::new(HashMap::<u32, BoundGlobal>::new()));
 
+    // Profiling has a cost on the remote so Profiler globals are only
+    // bound when selected. Their registry info is kept to bind them later.
+    let profilers = Rc::new(RefCell::new(HashMap::<
+        u32,
+        pw::registry::GlobalObject<pw::properties::PropertiesBox>,
+    >::new()));
+    let selected_profiler = Rc::new(Cell::new(None));
+
+    // Streams measuring levels, by the ID of the node they capture from
+    let meters = Rc::new(RefCell::new(HashMap::<u32, Meter>::new()));
+
     let (mainloop, context, connection, registry): (
         pw::main_loop::MainLoopRc,
         pw::context::ContextRc,
@@ -103,6 +120,9 @@ pub fn pipewire_thread(
 
         let locals = Rc::clone(&locals);
         let binds = Rc::clone(&binds);
+        let profilers = Rc::clone(&profilers);
+        let selected_profiler = Rc::clone(&selected_profiler);
+        let meters = Rc::clone(&meters);
 
         move |msg| match msg {
             Request::Stop => {
@@ -195,6 +215,47 @@ pub fn pipewire_thread(
                     object.call(method);
                 }
             }
+            Request::SelectProfiler(id) => {
+                // Dropping the proxy unbinds the profiler
+                if let Some(previous) = selected_profiler.replace(id) {
+                    binds.borrow_mut().remove(&previous);
+                }
+
+                let Some(id) = id else {
+                    return;
+                };
+
+                if let Some(global) = profilers.borrow().get(&id) {
+                    let proxy_removed = {
+                        let binds = Rc::clone(&binds);
+                        move || {
+                            binds.borrow_mut().remove(&id);
+                        }
+                    };
+
+                    match BoundGlobal::bind_to(&registry, global, send.clone(), proxy_removed) {
+                        Ok(bound_global) => {
+                            binds.borrow_mut().insert(id, bound_global);
+                        }
+                        Err(e) => {
+                            eprintln!("Error binding profiler {id}: {e}");
+                        }
+                    }
+                }
+            }
+            Request::StartMeter { node, props } => {
+                match Meter::new(&core, node, props, send.clone()) {
+                    Ok(meter) => {
+                        meters.borrow_mut().insert(node, meter);
+                    }
+                    Err(e) => {
+                        eprintln!("Error creating meter of node {node}: {e}");
+                    }
+                }
+            }
+            Request::StopMeter(node) => {
+                meters.borrow_mut().remove(&node);
+            }
         }
     });
 
@@ -256,6 +317,8 @@ pub fn pipewire_thread(
             let send = send.clone();
             let registry = registry.clone();
             let binds = Rc::clone(&binds);
+            let profilers = Rc::clone(&profilers);
+            let selected_profiler = Rc::clone(&selected_profiler);
             move |global| {
                 if global.id == 0 {
                     return;
@@ -268,6 +331,15 @@ pub fn pipewire_thread(
                 ));
 
                 let id = global.id;
+
+                if global.type_ == ObjectType::Profiler {
+                    profilers.borrow_mut().insert(id, global.to_owned());
+
+                    if selected_profiler.get() != Some(id) {
+                        return;
+                    }
+                }
+
                 let proxy_removed = {
                     let binds = binds.clone();
                     move || {
@@ -286,7 +358,12 @@ pub fn pipewire_thread(
         })
         .global_remove({
             let send = send.clone();
+            let profilers = Rc::clone(&profilers);
+            let meters = Rc::clone(&meters);
             move |id| {
+                profilers.borrow_mut().remove(&id);
+                meters.borrow_mut().remove(&id);
+
                 send(Event::GlobalRemoved(id));
             }
         })
@@ -18,7 +18,7 @@
 
 use pipewire::spa::{self, pod::deserialize::*, utils::Fraction};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Info {
     pub counter: i64,
     pub cpu_load_fast: f32,
@@ -68,7 +68,7 @@ impl<'de> PodDeserialize<'de> for Info {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Clock {
     pub flags: i32,
     pub id: i32,
@@ -208,7 +208,7 @@ impl<'de> PodDeserialize<'de> for NodeBlock {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Profiling {
     pub info: Info,
     pub clock: Clock,
@@ -349,6 +349,63 @@ impl<'de> PodDeserialize<'de> for Profiling {
     }
 }
 
+#[cfg(test)]
+impl NodeBlock {
+    pub fn test(id: i32, signal: i64, awake: i64, finish: i64) -> Self {
+        Self {
+            id,
+            name: format!("node{id}"),
+            prev_signal: 0,
+            signal,
+            awake,
+            finish,
+            status: 3, // Finished
+            latency: Fraction { num: 0, denom: 0 },
+            xrun_count: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Profiling {
+    /// A cycle of driver 30 with a 1ms quantum that starts at `signal`,
+    /// 1ms after the previous one, and ends 200us later
+    pub fn test(counter: i64, signal: i64, followers: Vec<NodeBlock>) -> Self {
+        Self {
+            info: Info {
+                counter,
+                cpu_load_fast: 0.,
+                cpu_load_medium: 0.,
+                cpu_load_slow: 0.,
+                xrun_count: 0,
+            },
+            clock: Clock {
+                flags: 0,
+                id: 30,
+                name: String::new(),
+                nsec: signal,
+                rate: Fraction {
+                    num: 1,
+                    denom: 48000,
+                },
+                position: 0,
+                duration: 48,
+                delay: 0,
+                rate_diff: 1.,
+                next_nsec: signal + 1_000_000,
+                transport_state: None,
+                cycle: None,
+                xrun_duration: None,
+            },
+            driver: NodeBlock {
+                prev_signal: signal - 1_000_000,
+                ..NodeBlock::test(30, signal, signal, signal + 200_000)
+            },
+            followers,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Profilings(pub Vec<Profiling>);
 
@@ -0,0 +1,576 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eframe::egui;
+use pipewire::types::ObjectType;
+
+use crate::{
+    backend::{self, pods::profiler::Profiling},
+    ui::{
+        globals_store::Global,
+        util::{RingBuf, tool::Tool},
+    },
+};
+
+const MAX_ALERTS: usize = 1000;
+
+/// Node states that aren't errors. Any other state is the error message.
+const NODE_STATES: [&str; 4] = ["Creating", "Idle", "Suspended", "Running"];
+
+#[derive(Clone, PartialEq)]
+enum Condition {
+    /// Busy time of a node over the quantum is above the threshold.
+    /// An empty node matches every node.
+    BusyRatio {
+        node: String,
+        threshold: f64,
+    },
+    /// Driver end date over the quantum is above the threshold
+    DriverEndDate {
+        threshold: f64,
+    },
+    XrunIncrease,
+    NodeError,
+}
+
+impl Condition {
+    const fn kind(&self) -> &'static str {
+        match self {
+            Self::BusyRatio { .. } => "Busy/Quantum above",
+            Self::DriverEndDate { .. } => "Driver end date above quantum percentage",
+            Self::XrunIncrease => "Xrun count increased",
+            Self::NodeError => "Node state became error",
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Self::BusyRatio { node, threshold } => {
+                let node = if node.is_empty() { "any node" } else { node };
+                format!("Busy/Quantum of {node} above {threshold}")
+            }
+            Self::DriverEndDate { threshold } => {
+                format!("Driver end date above {}% of the quantum", threshold * 100.)
+            }
+            Self::XrunIncrease | Self::NodeError => self.kind().to_owned(),
+        }
+    }
+}
+
+struct Rule {
+    condition: Condition,
+    enabled: bool,
+    pause_profiler: bool,
+
+    /// IDs of the objects the condition is currently true for.
+    /// Used to fire only when the condition becomes true instead of on every cycle.
+    active: HashSet<i32>,
+}
+
+impl Rule {
+    /// Sets whether the condition is true for the object
+    /// and returns whether it just became true
+    fn transition(&mut self, id: i32, triggered: bool) -> bool {
+        if triggered {
+            self.active.insert(id)
+        } else {
+            self.active.remove(&id);
+            false
+        }
+    }
+
+    /// Evaluates the rule on a profiling, returning the messages of the fired alerts.
+    /// `prev_xruns` is the previous xrun count of the profiling's driver, if known.
+    fn check_profiling(&mut self, p: &Profiling, prev_xruns: Option<i32>) -> Vec<String> {
+        let quantum = p.clock.duration as f64 * f64::from(p.clock.rate.num) * 1_000_000_000.
+            / f64::from(p.clock.rate.denom);
+
+        let mut fired = Vec::new();
+
+        match &self.condition {
+            Condition::BusyRatio { node, threshold } => {
+                let threshold = *threshold;
+                let node = node.clone();
+
+                for nb in std::iter::once(&p.driver).chain(p.followers.iter()) {
+                    if !node.is_empty() && nb.name != node && nb.id.to_string() != node {
+                        continue;
+                    }
+
+                    // Not completed
+                    if nb.finish < nb.awake {
+                        continue;
+                    }
+
+                    let ratio = (nb.finish - nb.awake) as f64 / quantum;
+
+                    if self.transition(nb.id, ratio > threshold) {
+                        fired.push(format!(
+                            "{} ({}) Busy/Quantum is {ratio:.3}",
+                            nb.name, nb.id
+                        ));
+                    }
+                }
+            }
+            Condition::DriverEndDate { threshold } => {
+                let ratio = (p.driver.finish - p.driver.signal) as f64 / quantum;
+                let triggered = ratio > *threshold;
+
+                if self.transition(p.driver.id, triggered) {
+                    fired.push(format!(
+                        "Driver {} ({}) end date is {:.1}% of the quantum",
+                        p.driver.name,
+                        p.driver.id,
+                        ratio * 100.
+                    ));
+                }
+            }
+            Condition::XrunIncrease => {
+                if let Some(prev) = prev_xruns
+                    && p.info.xrun_count > prev
+                {
+                    fired.push(format!(
+                        "Driver {} ({}) xrun count increased from {prev} to {}",
+                        p.driver.name, p.driver.id, p.info.xrun_count
+                    ));
+                }
+            }
+            Condition::NodeError => {}
+        }
+
+        fired
+    }
+}
+
+struct Alert {
+    time: SystemTime,
+    rule: String,
+    message: String,
+}
+
+/// Seconds since the epoch, and the time of day in UTC
+fn format_time(time: SystemTime) -> (u64, String) {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let day = secs % 86400;
+
+    (
+        secs,
+        format!(
+            "{:02}:{:02}:{:02} UTC",
+            day / 3600,
+            (day % 3600) / 60,
+            day % 60
+        ),
+    )
+}
+
+/// Rules that are evaluated on profiler data and object info changes
+pub struct Alerts {
+    rules: Vec<Rule>,
+    alerts: RingBuf<Alert>,
+
+    /// Alerts fired since the last time [`Alerts::take_fired`] was called
+    fired: usize,
+
+    xrun_counts: HashMap<i32, i32>,
+
+    log_path: String,
+    log: Option<File>,
+    log_status: Option<String>,
+
+    new_condition: Condition,
+    new_pause_profiler: bool,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            alerts: RingBuf::with_capacity(MAX_ALERTS),
+
+            fired: 0,
+
+            xrun_counts: HashMap::new(),
+
+            log_path: String::from("alerts.log"),
+            log: None,
+            log_status: None,
+
+            new_condition: Condition::BusyRatio {
+                node: String::new(),
+                threshold: 0.8,
+            },
+            new_pause_profiler: false,
+        }
+    }
+}
+
+impl Tool for Alerts {
+    const NAME: &'static str = "Alerts";
+
+    fn show(&mut self, ui: &mut egui::Ui, _sx: &backend::Sender) {
+        self.show_alerts(ui);
+    }
+}
+
+impl Alerts {
+    fn fire(&mut self, rule: String, message: String) {
+        let alert = Alert {
+            time: SystemTime::now(),
+            rule,
+            message,
+        };
+
+        if let Some(log) = &mut self.log {
+            let (secs, _) = format_time(alert.time);
+            if let Err(e) = writeln!(log, "{secs}\t{}\t{}", alert.rule, alert.message) {
+                self.log_status = Some(format!("Logging stopped: {e}"));
+                self.log = None;
+            }
+        }
+
+        self.alerts.push_back(MAX_ALERTS, alert);
+        self.fired += 1;
+    }
+
+    /// Evaluates the rules on new profiler data.
+    /// Returns whether a rule that pauses the profiler fired.
+    pub fn check_profilings(&mut self, profilings: &[Profiling]) -> bool {
+        let mut pause = false;
+
+        for p in profilings {
+            let prev_xruns = self.xrun_counts.insert(p.driver.id, p.info.xrun_count);
+
+            let mut fired = Vec::new();
+            for rule in self.rules.iter_mut().filter(|r| r.enabled) {
+                let messages = rule.check_profiling(p, prev_xruns);
+
+                if !messages.is_empty() {
+                    pause |= rule.pause_profiler;
+                    fired.extend(
+                        messages
+                            .into_iter()
+                            .map(|m| (rule.condition.description(), m)),
+                    );
+                }
+            }
+
+            for (rule, message) in fired {
+                self.fire(rule, message);
+            }
+        }
+
+        pause
+    }
+
+    /// Evaluates the rules on new info of a global, before it's set.
+    /// Returns whether a rule that pauses the profiler fired.
+    pub fn check_global_info(&mut self, global: &Global, info: &[(&'static str, String)]) -> bool {
+        if *global.object_type() != ObjectType::Node {
+            return false;
+        }
+
+        let state = |info: &[(&'static str, String)]| {
+            info.iter()
+                .find(|(k, _)| *k == "State")
+                .map(|(_, v)| v.clone())
+        };
+
+        let Some(new_state) = state(info) else {
+            return false;
+        };
+
+        if NODE_STATES.contains(&new_state.as_str())
+            || global.info().and_then(state).as_ref() == Some(&new_state)
+        {
+            return false;
+        }
+
+        let message = format!(
+            "{} ({}) state became error: {new_state}",
+            global.name().map_or("Node", String::as_str),
+            global.id()
+        );
+
+        let mut pause = false;
+        let mut fired = Vec::new();
+        for rule in self
+            .rules
+            .iter()
+            .filter(|r| r.enabled && r.condition == Condition::NodeError)
+        {
+            pause |= rule.pause_profiler;
+            fired.push(rule.condition.description());
+        }
+
+        for rule in fired {
+            self.fire(rule, message.clone());
+        }
+
+        pause
+    }
+
+    /// Returns whether alerts were fired since the last call
+    pub fn take_fired(&mut self) -> bool {
+        std::mem::take(&mut self.fired) != 0
+    }
+
+    fn start_logging(&mut self) -> io::Result<()> {
+        self.log = Some(
+            File::options()
+                .create(true)
+                .append(true)
+                .open(&self.log_path)?,
+        );
+
+        Ok(())
+    }
+
+    fn show_rule_editor(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::new("condition", "Condition")
+            .selected_text(self.new_condition.kind())
+            .show_ui(ui, |ui| {
+                for condition in [
+                    Condition::BusyRatio {
+                        node: String::new(),
+                        threshold: 0.8,
+                    },
+                    Condition::DriverEndDate { threshold: 0.9 },
+                    Condition::XrunIncrease,
+                    Condition::NodeError,
+                ] {
+                    let selected = std::mem::discriminant(&self.new_condition)
+                        == std::mem::discriminant(&condition);
+
+                    if ui.selectable_label(selected, condition.kind()).clicked() && !selected {
+                        self.new_condition = condition;
+                    }
+                }
+            });
+
+        match &mut self.new_condition {
+            Condition::BusyRatio { node, threshold } => {
+                ui.horizontal(|ui| {
+                    ui.label("Node");
+                    egui::TextEdit::singleline(node)
+                        .hint_text("Name or ID, empty for any")
+                        .show(ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Threshold");
+                    ui.add(egui::DragValue::new(threshold).range(0.0..=10.).speed(0.01));
+                });
+            }
+            Condition::DriverEndDate { threshold } => {
+                let mut percentage = *threshold * 100.;
+                ui.horizontal(|ui| {
+                    ui.label("Threshold");
+                    ui.add(
+                        egui::DragValue::new(&mut percentage)
+                            .range(0.0..=1000.)
+                            .suffix("%"),
+                    );
+                });
+                *threshold = percentage / 100.;
+            }
+            Condition::XrunIncrease | Condition::NodeError => {}
+        }
+
+        ui.checkbox(&mut self.new_pause_profiler, "Pause the profiler");
+
+        if ui.button("Add rule").clicked() {
+            self.rules.push(Rule {
+                condition: self.new_condition.clone(),
+                enabled: true,
+                pause_profiler: self.new_pause_profiler,
+                active: HashSet::new(),
+            });
+        }
+    }
+
+    fn show_alerts(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("New rule").show(ui, |ui| {
+            self.show_rule_editor(ui);
+        });
+
+        ui.separator();
+
+        ui.heading("Rules");
+
+        if self.rules.is_empty() {
+            ui.label("No rules");
+        } else {
+            let mut delete = None;
+
+            egui::Grid::new("rules")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (i, rule) in self.rules.iter_mut().enumerate() {
+                        ui.checkbox(&mut rule.enabled, "").on_hover_text("Enabled");
+                        ui.label(rule.condition.description());
+                        ui.checkbox(&mut rule.pause_profiler, "Pause profiler");
+                        if ui.small_button("Delete").clicked() {
+                            delete = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            if let Some(i) = delete {
+                self.rules.remove(i);
+            }
+        }
+
+        ui.separator();
+
+        egui::CollapsingHeader::new("Log").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                egui::TextEdit::singleline(&mut self.log_path)
+                    .hint_text("alerts.log")
+                    .desired_width(f32::INFINITY)
+                    .show(ui);
+            });
+
+            ui.horizontal(|ui| {
+                if self.log.is_some() {
+                    if ui.button("Stop logging").clicked() {
+                        self.log = None;
+                        self.log_status = None;
+                    } else {
+                        ui.label("Logging");
+                    }
+                    return;
+                }
+
+                ui.add_enabled_ui(!self.log_path.is_empty(), |ui| {
+                    if ui
+                        .button("Start logging")
+                        .on_hover_text(
+                            "Append fired alerts to the file, one per line as\n\
+                            seconds since the epoch, rule and message separated by tabs",
+                        )
+                        .on_disabled_hover_text("Provide a path first")
+                        .clicked()
+                    {
+                        self.log_status = self
+                            .start_logging()
+                            .err()
+                            .map(|e| format!("Failed to open {}: {e}", self.log_path));
+                    }
+                });
+            });
+
+            if let Some(status) = &self.log_status {
+                ui.label(status);
+            }
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.heading(format!("Fired ({})", self.alerts.len()));
+            if ui.small_button("Clear").clicked() {
+                self.alerts.clear();
+            }
+        });
+
+        egui::Grid::new("alerts")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Time");
+                ui.label("Rule");
+                ui.label("Message");
+                ui.end_row();
+
+                // Newest first
+                for alert in self.alerts.iter().rev() {
+                    ui.label(format_time(alert.time).1);
+                    ui.label(&alert.rule);
+                    ui.label(&alert.message);
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::pods::profiler::NodeBlock;
+
+    /// A cycle with a follower named "node" that was busy for `busy` nanoseconds of the 1ms quantum
+    fn profiling(busy: i64, xrun_count: i32) -> Profiling {
+        let node = NodeBlock {
+            name: String::from("node"),
+            ..NodeBlock::test(40, 0, 0, busy)
+        };
+
+        let mut p = Profiling::test(0, 0, vec![node]);
+        p.info.xrun_count = xrun_count;
+        p
+    }
+
+    fn rule(condition: Condition) -> Rule {
+        Rule {
+            condition,
+            enabled: true,
+            pause_profiler: false,
+            active: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn fires_when_becoming_true() {
+        let mut rule = rule(Condition::BusyRatio {
+            node: String::from("node"),
+            threshold: 0.8,
+        });
+
+        assert!(
+            rule.check_profiling(&profiling(500_000, 0), None)
+                .is_empty()
+        );
+        assert_eq!(rule.check_profiling(&profiling(900_000, 0), None).len(), 1);
+        assert!(
+            rule.check_profiling(&profiling(950_000, 0), None)
+                .is_empty()
+        );
+        assert!(
+            rule.check_profiling(&profiling(100_000, 0), None)
+                .is_empty()
+        );
+        assert_eq!(rule.check_profiling(&profiling(900_000, 0), None).len(), 1);
+    }
+
+    #[test]
+    fn xrun_increase() {
+        let mut rule = rule(Condition::XrunIncrease);
+
+        assert!(rule.check_profiling(&profiling(0, 2), None).is_empty());
+        assert!(rule.check_profiling(&profiling(0, 2), Some(2)).is_empty());
+        assert_eq!(rule.check_profiling(&profiling(0, 3), Some(2)).len(), 1);
+    }
+}
@@ -34,6 +34,8 @@ pub enum View {
     Profiler = 1 << 1,
     ProcessViewer = 1 << 2,
     Graph = 1 << 3,
+    Applications = 1 << 4,
+    Clock = 1 << 5,
 }
 
 impl View {
@@ -43,6 +45,8 @@ impl View {
             Self::ProcessViewer => "Process Viewer",
             Self::GlobalTracker => "Global Tracker",
             Self::Graph => "Graph",
+            Self::Applications => "Applications",
+            Self::Clock => "Clock",
         }
     }
 }
@@ -74,7 +78,8 @@ mod inspector {
     use crate::{
         backend::{self, Event, RemoteInfo},
         ui::{
-            ContextManager, GlobalsStore, Graph, MetadataEditor, ObjectCreator, Profiler,
+            Alerts, Applications, ContextManager, GlobalsStore, Graph, MetadataEditor,
+            ObjectCreator, Profiler,
             globals_store::ObjectData,
             util::{persistence::PersistentView, tool::Windowed},
         },
@@ -91,6 +96,7 @@ mod inspector {
     )]
     pub struct PersistentData {
         graph: Option<<Graph as PersistentView>::Data>,
+        profiler: Option<<Profiler as PersistentView>::Data>,
     }
 
     /// Holds all of the UIs, and their states, for interacting with PipeWire.
@@ -101,10 +107,12 @@ mod inspector {
         globals: GlobalsStore,
         profiler: Profiler,
         graph: Graph,
+        applications: Applications,
 
         object_creator: Windowed<ObjectCreator>,
         metadata_editor: Windowed<MetadataEditor>,
         context_manager: Windowed<ContextManager>,
+        alerts: Windowed<Alerts>,
     }
 
     impl Inspector {
@@ -118,20 +126,25 @@ mod inspector {
                 handle: backend::Handle::run(remote, mainloop_properties, context_properties),
 
                 globals: GlobalsStore::new(),
-                profiler: Profiler::with_max_profilings(250),
+                profiler: restore_data
+                    .and_then(|data| data.profiler.as_ref())
+                    .map_or_else(|| Profiler::with_max_profilings(250), Profiler::with_data),
                 graph: restore_data
                     .and_then(|data| data.graph.as_ref())
                     .map_or_else(Graph::new, Graph::with_data),
+                applications: Applications::new(),
 
                 object_creator: Windowed::default(),
                 metadata_editor: Windowed::default(),
                 context_manager: Windowed::default(),
+                alerts: Windowed::default(),
             }
         }
 
         pub fn save_data(&self, data: &mut Option<PersistentData>) {
             let new_data = PersistentData {
                 graph: self.graph.save_data(),
+                profiler: self.profiler.save_data(),
             };
 
             match data {
@@ -139,6 +152,9 @@ mod inspector {
                     if let Some(graph) = new_data.graph {
                         data.graph = Some(graph);
                     }
+                    if let Some(profiler) = new_data.profiler {
+                        data.profiler = Some(profiler);
+                    }
                 }
                 None => *data = Some(new_data),
             }
@@ -167,6 +183,16 @@ mod inspector {
                         "Performance measurements of running nodes",
                     ),
                     (View::Graph, "🖧 Graph", "Visual representation of the graph"),
+                    (
+                        View::Applications,
+                        "👥 Applications",
+                        "Clients and their objects grouped by application",
+                    ),
+                    (
+                        View::Clock,
+                        "🕐 Clock",
+                        "Clock drift, delay and period jitter of the drivers",
+                    ),
                 ] {
                     let open = open_tabs & tab as u8 != 0;
 
@@ -201,6 +227,11 @@ mod inspector {
                         "🗄 Context Manager",
                         "Manage the PipeWire context",
                     ),
+                    (
+                        &mut self.alerts.open,
+                        "🔔 Alerts",
+                        "Get notified when profiler measurements or node states cross thresholds",
+                    ),
                 ] {
                     ui.toggle_value(open, name).on_hover_text(description);
                 }
@@ -211,6 +242,14 @@ mod inspector {
             self.object_creator.window(ctx, &self.handle.sx);
             self.metadata_editor.window(ctx, &self.handle.sx);
             self.context_manager.window(ctx, &self.handle.sx);
+
+            if self.alerts.tool.take_fired() {
+                self.alerts.open = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                    egui::UserAttentionType::Critical,
+                ));
+            }
+            self.alerts.window(ctx, &self.handle.sx);
         }
 
         #[must_use = "Indicates whether the connection to the backend has ended"]
@@ -241,6 +280,10 @@ mod inspector {
                     let global = self.globals.add_global(id, object_type, props);
                     let global_borrow = global.borrow();
 
+                    if *global_borrow.object_type() == ObjectType::Profiler {
+                        self.profiler.add_source(id, global, &self.handle.sx);
+                    }
+
                     if global_borrow.props().is_empty() {
                         return;
                     }
@@ -275,6 +318,9 @@ mod inspector {
                             ObjectType::Link => {
                                 self.graph.remove_link(id);
                             }
+                            ObjectType::Profiler => {
+                                self.profiler.remove_source(id, &self.handle.sx);
+                            }
                             _ => {}
                         }
                     }
@@ -288,6 +334,12 @@ mod inspector {
                         return;
                     };
 
+                    if let Some(info) = &info
+                        && self.alerts.tool.check_global_info(&global.borrow(), info)
+                    {
+                        self.profiler.pause();
+                    }
+
                     // Add to graph
                     {
                         let global_borrow = global.borrow();
@@ -321,11 +373,11 @@ mod inspector {
                                     )
                                 {
                                     self.graph.add_link(
+                                        global,
                                         output_node,
                                         output_port,
                                         input_node,
                                         input_port,
-                                        id,
                                     );
                                 }
                             }
@@ -344,7 +396,14 @@ mod inspector {
                     *port.borrow_mut().object_data_mut() = ObjectData::Port(media_type);
                 }
                 Event::ProfilerProfile(samples) => {
+                    let pause = self.alerts.tool.check_profilings(&samples);
+
                     self.profiler.add_profilings(samples);
+
+                    // Pause after adding so that the cycles that fired are kept
+                    if pause {
+                        self.profiler.pause();
+                    }
                 }
                 Event::MetadataProperty {
                     id,
@@ -379,6 +438,9 @@ mod inspector {
                 Event::ContextProperties(properties) => {
                     self.context_manager.tool.set_context_properties(properties);
                 }
+                Event::Levels { node, levels } => {
+                    self.graph.set_levels(node, levels);
+                }
                 Event::Stop => unreachable!(),
             }
         }
@@ -407,12 +469,32 @@ mod inspector {
                         },
                     );
                 }
+                View::Clock => {
+                    self.profiler
+                        .show_clock(ui, &self.handle.sx, settings.update_rate, |id| {
+                            id.try_into()
+                                .ok()
+                                .and_then(|id| self.globals.get_global(id))
+                                .map(Rc::downgrade)
+                        });
+                }
                 View::GlobalTracker => {
                     self.globals.show(ui, &self.handle.sx);
                 }
                 View::Graph => {
                     self.graph.show(ui, &self.handle.sx);
                 }
+                View::Applications => {
+                    let loads = self.profiler.node_loads(settings.update_rate, |id| {
+                        id.try_into()
+                            .ok()
+                            .and_then(|id| self.globals.get_global(id))
+                            .map(Rc::downgrade)
+                    });
+
+                    self.applications
+                        .show(ui, self.globals.globals(), &loads, &self.handle.sx);
+                }
             }
         }
     }
@@ -0,0 +1,361 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use eframe::egui;
+use pipewire::types::ObjectType;
+
+use crate::{
+    backend,
+    ui::{
+        globals_store::Global,
+        profiler::{NodeLoad, format_to_time},
+        util::uis::global_info_button,
+    },
+};
+
+/// Name and process ID that identify an application
+type AppKey = (String, Option<String>);
+
+fn app_key(props: &BTreeMap<String, String>) -> Option<AppKey> {
+    props
+        .get("application.name")
+        .map(|name| (name.clone(), props.get("application.process.id").cloned()))
+}
+
+fn prop_id(global: &Global, key: &str) -> Option<u32> {
+    global.props().get(key).and_then(|id| id.parse().ok())
+}
+
+fn title(global: &Global) -> String {
+    global.name().map_or_else(
+        || global.id().to_string(),
+        |name| format!("{name} ({})", global.id()),
+    )
+}
+
+#[derive(Default)]
+struct Node {
+    global: Option<Rc<RefCell<Global>>>,
+    ports: Vec<Rc<RefCell<Global>>>,
+}
+
+#[derive(Default)]
+struct Application {
+    clients: Vec<Rc<RefCell<Global>>>,
+    nodes: BTreeMap<u32, Node>,
+    /// Links with at least one of their ends on a node of the application
+    links: BTreeMap<u32, Rc<RefCell<Global>>>,
+    /// Names of the devices that the application's nodes are linked to
+    devices: BTreeSet<String>,
+
+    /// Sum of the times the application's nodes were busy in their last cycle, in nanoseconds
+    busy: i64,
+    /// Sum of the busy times as fractions of the quantums
+    busy_quantum: f64,
+    /// Sum of the xruns counted by the nodes that report them
+    xruns: Option<i32>,
+    profiled_nodes: usize,
+}
+
+/// Clients and the objects they own, grouped by the application they belong to
+pub struct Applications {
+    search: String,
+}
+
+impl Applications {
+    pub const fn new() -> Self {
+        Self {
+            search: String::new(),
+        }
+    }
+
+    fn group<'a>(
+        globals: impl Iterator<Item = &'a Rc<RefCell<Global>>>,
+        loads: &HashMap<i32, NodeLoad>,
+    ) -> BTreeMap<AppKey, Application> {
+        let mut clients = Vec::new();
+        let mut nodes = HashMap::new();
+        let mut ports = Vec::new();
+        let mut links = Vec::new();
+        let mut devices = HashMap::new();
+
+        for global in globals {
+            let borrow = global.borrow();
+            match borrow.object_type() {
+                ObjectType::Client => clients.push(global),
+                ObjectType::Node => {
+                    nodes.insert(borrow.id(), global);
+                }
+                ObjectType::Port => ports.push(global),
+                ObjectType::Link => links.push(global),
+                ObjectType::Device => {
+                    devices.insert(borrow.id(), global);
+                }
+                _ => {}
+            }
+        }
+
+        let mut apps = BTreeMap::<AppKey, Application>::new();
+        let mut client_apps = HashMap::new();
+
+        for client in clients {
+            let borrow = client.borrow();
+            let key = app_key(borrow.props()).unwrap_or_else(|| (title(&borrow), None));
+
+            client_apps.insert(borrow.id(), key.clone());
+            apps.entry(key).or_default().clients.push(Rc::clone(client));
+        }
+
+        // Nodes belong to the application of their client
+        let mut node_apps = HashMap::new();
+        for (&id, node) in &nodes {
+            let borrow = node.borrow();
+            let Some(key) = prop_id(&borrow, "client.id")
+                .and_then(|client| client_apps.get(&client).cloned())
+                .or_else(|| app_key(borrow.props()))
+            else {
+                continue;
+            };
+
+            let app = apps.entry(key.clone()).or_default();
+            app.nodes.entry(id).or_default().global = Some(Rc::clone(node));
+
+            if let Some(load) = i32::try_from(id).ok().and_then(|id| loads.get(&id)) {
+                app.busy += load.busy;
+                app.busy_quantum += load.busy_quantum;
+                if let Some(xruns) = load.xruns {
+                    *app.xruns.get_or_insert(0) += xruns;
+                }
+                app.profiled_nodes += 1;
+            }
+
+            node_apps.insert(id, key);
+        }
+
+        for port in ports {
+            let Some(node) = prop_id(&port.borrow(), "node.id") else {
+                continue;
+            };
+
+            if let Some(node_app) = node_apps.get(&node)
+                && let Some(app) = apps.get_mut(node_app)
+            {
+                app.nodes
+                    .entry(node)
+                    .or_default()
+                    .ports
+                    .push(Rc::clone(port));
+            }
+        }
+
+        let device_name = |node: u32| {
+            let device = prop_id(&nodes.get(&node)?.borrow(), "device.id")?;
+            devices.get(&device).map(|device| title(&device.borrow()))
+        };
+
+        for link in links {
+            let borrow = link.borrow();
+            let ends = [
+                prop_id(&borrow, "link.output.node"),
+                prop_id(&borrow, "link.input.node"),
+            ];
+
+            for (end, other) in [(ends[0], ends[1]), (ends[1], ends[0])] {
+                let Some(app) = end
+                    .and_then(|end| node_apps.get(&end))
+                    .and_then(|key| apps.get_mut(key))
+                else {
+                    continue;
+                };
+
+                app.links.insert(borrow.id(), Rc::clone(link));
+
+                if let Some(device) = other.and_then(device_name) {
+                    app.devices.insert(device);
+                }
+            }
+        }
+
+        apps
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        globals: impl Iterator<Item = &Rc<RefCell<Global>>>,
+        loads: &HashMap<i32, NodeLoad>,
+        sx: &backend::Sender,
+    ) {
+        let apps = Self::group(globals, loads);
+
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            egui::TextEdit::singleline(&mut self.search)
+                .hint_text("Application name")
+                .show(ui);
+        });
+
+        let search = self.search.to_lowercase();
+        let apps: Vec<_> = apps
+            .iter()
+            .filter(|((name, _), _)| name.to_lowercase().contains(&search))
+            .collect();
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::ScrollArea::horizontal()
+                .id_salt("totals")
+                .show(ui, |ui| {
+                    egui::Grid::new("applications")
+                        .striped(true)
+                        .num_columns(8)
+                        .show(ui, |ui| {
+                            ui.label("Application");
+                            ui.label("PID");
+                            ui.label("Clients");
+                            ui.label("Nodes");
+                            ui.label("Busy")
+                                .on_hover_text("Sum of the times the nodes were busy processing in their last cycle");
+                            ui.label("Busy/Quantum")
+                                .on_hover_text("Sum of the loads of the nodes");
+                            ui.label("Xruns")
+                                .on_hover_text("Sum of the xruns counted by the nodes themselves");
+                            ui.label("Devices")
+                                .on_hover_text("Devices that the nodes are linked to");
+                            ui.end_row();
+
+                            for ((name, pid), app) in &apps {
+                                ui.label(name);
+                                ui.label(pid.as_deref().unwrap_or("-"));
+                                ui.label(app.clients.len().to_string());
+                                ui.label(app.nodes.len().to_string());
+
+                                if app.profiled_nodes == 0 {
+                                    for _ in 0..3 {
+                                        ui.label("-");
+                                    }
+                                } else {
+                                    ui.label(format_to_time(app.busy));
+                                    ui.label(format!("{:.6}", app.busy_quantum));
+                                    ui.label(
+                                        app.xruns.map_or_else(|| "-".to_owned(), |x| x.to_string()),
+                                    );
+                                }
+
+                                ui.label(
+                                    app.devices
+                                        .iter()
+                                        .map(String::as_str)
+                                        .collect::<Vec<_>>()
+                                        .join(", "),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            ui.separator();
+
+            for ((name, pid), app) in apps {
+                let heading = pid
+                    .as_ref()
+                    .map_or_else(|| name.clone(), |pid| format!("{name} (PID {pid})"));
+
+                egui::CollapsingHeader::new(heading)
+                    .id_salt((name, pid))
+                    .show(ui, |ui| {
+                        Self::show_application(ui, app, sx);
+                    });
+            }
+        });
+    }
+
+    fn show_application(ui: &mut egui::Ui, app: &Application, sx: &backend::Sender) {
+        fn row(ui: &mut egui::Ui, global: &Rc<RefCell<Global>>, sx: &backend::Sender) {
+            ui.horizontal(|ui| {
+                global_info_button(ui, Some(global), sx);
+                ui.label(title(&global.borrow()));
+            });
+        }
+
+        if !app.clients.is_empty() {
+            ui.label("Clients");
+            ui.indent("clients", |ui| {
+                for client in &app.clients {
+                    row(ui, client, sx);
+                }
+            });
+        }
+
+        if !app.nodes.is_empty() {
+            ui.label("Nodes");
+            ui.indent("nodes", |ui| {
+                for (id, node) in &app.nodes {
+                    let Some(global) = &node.global else {
+                        continue;
+                    };
+
+                    row(ui, global, sx);
+
+                    if !node.ports.is_empty() {
+                        egui::CollapsingHeader::new(format!("Ports ({})", node.ports.len()))
+                            .id_salt(("ports", id))
+                            .show(ui, |ui| {
+                                for port in &node.ports {
+                                    row(ui, port, sx);
+                                }
+                            });
+                    }
+                }
+            });
+        }
+
+        if !app.links.is_empty() {
+            ui.label("Links");
+            ui.indent("links", |ui| {
+                for link in app.links.values() {
+                    let borrow = link.borrow();
+                    let end = |node, port| {
+                        format!(
+                            "{}:{}",
+                            borrow.props().get(node).map_or("?", String::as_str),
+                            borrow.props().get(port).map_or("?", String::as_str)
+                        )
+                    };
+                    let text = format!(
+                        "{} ({} → {})",
+                        borrow.id(),
+                        end("link.output.node", "link.output.port"),
+                        end("link.input.node", "link.input.port")
+                    );
+                    drop(borrow);
+
+                    ui.horizontal(|ui| {
+                        global_info_button(ui, Some(link), sx);
+                        ui.label(text);
+                    });
+                }
+            });
+        }
+    }
+}
@@ -122,6 +122,10 @@ impl GlobalsStore {
         self.globals.get(&id)
     }
 
+    pub fn globals(&self) -> impl Iterator<Item = &Rc<RefCell<Global>>> {
+        self.globals.values()
+    }
+
     pub fn remove_global(&mut self, id: u32) -> Option<Rc<RefCell<Global>>> {
         self.filter_matches.remove(&id);
         self.globals.remove(&id)
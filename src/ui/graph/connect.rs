@@ -0,0 +1,167 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Pairing of the ports of two nodes to connect them all at once
+
+use super::Port;
+
+/// Nodes the user is connecting
+#[derive(Clone, Copy)]
+pub struct Pending {
+    pub from: u32,
+    /// [`None`] until the user chooses the node to connect to
+    pub to: Option<u32>,
+    /// Link single ports to all the ports of the other node
+    pub fan: bool,
+}
+
+/// What of a port matters for pairing it
+#[derive(Clone)]
+pub struct PortInfo {
+    pub id: u32,
+    /// `audio.channel`
+    pub channel: Option<String>,
+    /// `format.dsp`, ports are only paired with ports of the same format
+    pub format: Option<String>,
+    /// `port.id`, the order of the port in its node
+    pub index: u32,
+}
+
+impl PortInfo {
+    pub fn of(port: &Port) -> Self {
+        let global = port.global.borrow();
+        let props = global.props();
+
+        Self {
+            id: port.id,
+            channel: props
+                .get("audio.channel")
+                .filter(|channel| !channel.is_empty() && *channel != "UNK")
+                .cloned(),
+            format: props.get("format.dsp").cloned(),
+            index: props
+                .get("port.id")
+                .and_then(|index| index.parse().ok())
+                .unwrap_or(port.id),
+        }
+    }
+}
+
+/// Output and input port IDs to link.
+///
+/// Ports are paired by their channels, and the rest by their order.
+/// With `fan`, a single port on either side is linked to all the ports of the other side.
+pub fn pair(outputs: &[PortInfo], inputs: &[PortInfo], fan: bool) -> Vec<(u32, u32)> {
+    let mut outputs = outputs.to_vec();
+    let mut inputs = inputs.to_vec();
+    outputs.sort_by_key(|port| port.index);
+    inputs.sort_by_key(|port| port.index);
+
+    let mut pairs = Vec::new();
+
+    let mut formats: Vec<&Option<String>> = Vec::new();
+    for port in &outputs {
+        if !formats.contains(&&port.format) {
+            formats.push(&port.format);
+        }
+    }
+
+    for format in formats {
+        let outputs: Vec<&PortInfo> = outputs.iter().filter(|p| &p.format == format).collect();
+        let inputs: Vec<&PortInfo> = inputs.iter().filter(|p| &p.format == format).collect();
+
+        if fan && (outputs.len() == 1 || inputs.len() == 1) {
+            for output in &outputs {
+                for input in &inputs {
+                    pairs.push((output.id, input.id));
+                }
+            }
+            continue;
+        }
+
+        let mut used_inputs = vec![false; inputs.len()];
+        let mut unpaired_outputs = Vec::new();
+
+        for output in outputs {
+            let same_channel = inputs.iter().zip(&used_inputs).position(|(input, used)| {
+                !used && output.channel.is_some() && input.channel == output.channel
+            });
+
+            if let Some(i) = same_channel {
+                used_inputs[i] = true;
+                pairs.push((output.id, inputs[i].id));
+            } else {
+                unpaired_outputs.push(output);
+            }
+        }
+
+        let unpaired_inputs = inputs
+            .iter()
+            .zip(used_inputs)
+            .filter(|(_, used)| !used)
+            .map(|(input, _)| input);
+
+        for (output, input) in unpaired_outputs.into_iter().zip(unpaired_inputs) {
+            pairs.push((output.id, input.id));
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn port(id: u32, channel: &str) -> PortInfo {
+        PortInfo {
+            id,
+            channel: Some(channel.to_owned()),
+            format: Some("32 bit float mono audio".to_owned()),
+            index: id,
+        }
+    }
+
+    #[test]
+    fn pairs_by_channel_then_order() {
+        let outputs = [port(1, "FR"), port(2, "FL"), port(3, "AUX0")];
+        let inputs = [port(10, "FL"), port(11, "FR"), port(12, "AUX5")];
+
+        assert_eq!(pair(&outputs, &inputs, false), [(1, 11), (2, 10), (3, 12)]);
+    }
+
+    #[test]
+    fn fans_mono() {
+        let outputs = [port(1, "MONO")];
+        let inputs = [port(10, "FL"), port(11, "FR")];
+
+        assert_eq!(pair(&outputs, &inputs, false), [(1, 10)]);
+        assert_eq!(pair(&outputs, &inputs, true), [(1, 10), (1, 11)]);
+        assert_eq!(pair(&inputs, &outputs, true), [(10, 1), (11, 1)]);
+    }
+
+    #[test]
+    fn keeps_formats_apart() {
+        let mut midi = port(3, "");
+        midi.channel = None;
+        midi.format = Some("8 bit raw midi".to_owned());
+
+        let outputs = [port(1, "FL"), midi];
+        let inputs = [port(10, "FL"), port(11, "FR")];
+
+        assert_eq!(pair(&outputs, &inputs, false), [(1, 10)]);
+    }
+}
@@ -0,0 +1,456 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Writing of the graph as Graphviz DOT, like `pw-dot` does, and as SVG
+//! drawn with the positions of the nodes in the graph.
+
+#![allow(clippy::cast_precision_loss)]
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use eframe::egui;
+
+use super::{Link, Node, Port};
+
+/// Which nodes get exported
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    All,
+    /// Not hidden by the filters
+    Shown,
+    Selected,
+}
+
+impl Scope {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "All",
+            Self::Shown => "Shown",
+            Self::Selected => "Selected",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Dot,
+    Svg,
+}
+
+/// A node to export with its position in the graph, if it has one
+pub struct Item<'a> {
+    pub node: &'a Node,
+    pub pos: Option<egui::Pos2>,
+}
+
+pub struct Exporter {
+    path: String,
+    pub scope: Scope,
+    /// Include the node properties as tooltips
+    props: bool,
+    status: Option<String>,
+}
+
+impl Exporter {
+    pub const fn new() -> Self {
+        Self {
+            path: String::new(),
+            scope: Scope::Shown,
+            props: false,
+            status: None,
+        }
+    }
+
+    /// Returns the format the user chose to export to
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<Format> {
+        ui.horizontal(|ui| {
+            ui.label("Path");
+            egui::TextEdit::singleline(&mut self.path)
+                .hint_text("graph")
+                .show(ui)
+                .response
+                .on_hover_text("The extension of the format is added to it");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Nodes");
+            for scope in [Scope::All, Scope::Shown, Scope::Selected] {
+                ui.selectable_value(&mut self.scope, scope, scope.as_str());
+            }
+        });
+
+        ui.checkbox(&mut self.props, "Properties as tooltips");
+
+        let format = ui
+            .add_enabled_ui(!self.path.is_empty(), |ui| {
+                ui.horizontal(|ui| {
+                    let mut format = None;
+
+                    if ui
+                        .button("Export DOT")
+                        .on_hover_text("Ports are record fields, links are labeled with their state and format")
+                        .on_disabled_hover_text("Provide a path first")
+                        .clicked()
+                    {
+                        format = Some(Format::Dot);
+                    }
+
+                    if ui
+                        .button("Export SVG")
+                        .on_hover_text("Standalone drawing with the nodes where they are in the graph")
+                        .on_disabled_hover_text("Provide a path first")
+                        .clicked()
+                    {
+                        format = Some(Format::Svg);
+                    }
+
+                    format
+                })
+                .inner
+            })
+            .inner;
+
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+
+        format
+    }
+
+    pub fn export(&mut self, format: Format, items: &[Item], links: &[&Link]) {
+        let path = Path::new(&self.path).with_extension(match format {
+            Format::Dot => "dot",
+            Format::Svg => "svg",
+        });
+
+        let result = File::create(&path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            match format {
+                Format::Dot => write_dot(&mut out, items, links, self.props),
+                Format::Svg => write_svg(&mut out, items, links, self.props),
+            }?;
+            out.flush()
+        });
+
+        self.status = Some(match result {
+            Ok(()) => format!(
+                "Exported {} nodes and {} links to {}",
+                items.len(),
+                links.len(),
+                path.display()
+            ),
+            Err(e) => format!("Failed to export to {}: {e}", path.display()),
+        });
+    }
+}
+
+fn title(node: &Node) -> String {
+    format!("{} ({})", node.user_label, node.global.borrow().id())
+}
+
+fn info(link: &Link, key: &str) -> Option<String> {
+    link.global
+        .borrow()
+        .info()?
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.clone())
+}
+
+/// Lines of the properties of the node
+fn props(node: &Node) -> Vec<String> {
+    node.global
+        .borrow()
+        .props()
+        .iter()
+        .map(|(k, v)| format!("{k} = {v}"))
+        .collect()
+}
+
+/// Escapes a DOT quoted string
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapes the text of a DOT record field
+fn escape_record(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '{' | '}' | '|' | '<' | '>' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_dot(out: &mut impl Write, items: &[Item], links: &[&Link], props: bool) -> io::Result<()> {
+    let fields = |ports: &[Port]| {
+        ports
+            .iter()
+            .map(|port| format!("<p{}> {}", port.id, escape_record(&port.name)))
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+
+    writeln!(out, "digraph pipewire {{")?;
+    writeln!(out, "\trankdir=LR;")?;
+    writeln!(out, "\tnode [shape=record, fontname=\"sans-serif\"];")?;
+    writeln!(out, "\tedge [fontname=\"sans-serif\", fontsize=10];")?;
+
+    for Item { node, .. } in items {
+        // With left to right ranks, the braces lay the inputs, title and outputs next to each other
+        write!(
+            out,
+            "\tn{} [label=\"{{{{{}}}|{}|{{{}}}}}\"",
+            node.global.borrow().id(),
+            fields(&node.inputs),
+            escape_record(&title(node)),
+            fields(&node.outputs)
+        )?;
+
+        if props {
+            let tooltip = self::props(node)
+                .iter()
+                .map(|line| escape_dot(line))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            write!(out, ", tooltip=\"{tooltip}\"")?;
+        }
+
+        writeln!(out, "];")?;
+    }
+
+    for link in links {
+        let (output_node, output_port, input_node, input_port) = link.ends;
+
+        let label = ["State", "Format"]
+            .into_iter()
+            .filter_map(|key| info(link, key))
+            .map(|value| escape_dot(&value))
+            .collect::<Vec<_>>()
+            .join("\\n");
+
+        writeln!(
+            out,
+            "\tn{output_node}:p{output_port}:e -> n{input_node}:p{input_port}:w [label=\"{label}\"];"
+        )?;
+    }
+
+    writeln!(out, "}}")
+}
+
+const NODE_WIDTH: f32 = 280.;
+const HEADER_HEIGHT: f32 = 30.;
+const PORT_HEIGHT: f32 = 22.;
+const MARGIN: f32 = 40.;
+
+fn node_height(node: &Node) -> f32 {
+    HEADER_HEIGHT + PORT_HEIGHT * node.inputs.len().max(node.outputs.len()) as f32 + 8.
+}
+
+fn port_y(pos: egui::Pos2, index: usize) -> f32 {
+    pos.y + HEADER_HEIGHT + PORT_HEIGHT * (index as f32 + 0.5)
+}
+
+fn hex(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn write_svg(out: &mut impl Write, items: &[Item], links: &[&Link], props: bool) -> io::Result<()> {
+    // Nodes that were never placed go in a row below the rest
+    let bottom = items
+        .iter()
+        .filter_map(|item| Some(item.pos? + egui::vec2(0., node_height(item.node))))
+        .map(|pos| pos.y)
+        .fold(None, |max: Option<f32>, y| {
+            Some(max.map_or(y, |max| max.max(y)))
+        });
+    let left = items
+        .iter()
+        .filter_map(|item| item.pos)
+        .map(|pos| pos.x)
+        .fold(None, |min: Option<f32>, x| {
+            Some(min.map_or(x, |min| min.min(x)))
+        });
+
+    let mut next_unplaced = egui::pos2(
+        left.unwrap_or_default(),
+        bottom.map_or(0., |bottom| bottom + MARGIN),
+    );
+
+    let placed: Vec<(&Node, egui::Pos2)> = items
+        .iter()
+        .map(|item| {
+            let pos = item.pos.unwrap_or_else(|| {
+                let pos = next_unplaced;
+                next_unplaced.x += NODE_WIDTH + MARGIN;
+                pos
+            });
+            (item.node, pos)
+        })
+        .collect();
+
+    let Some(bounds) = placed
+        .iter()
+        .map(|(node, pos)| {
+            egui::Rect::from_min_size(*pos, egui::vec2(NODE_WIDTH, node_height(node)))
+        })
+        .reduce(egui::Rect::union)
+        .map(|bounds| bounds.expand(MARGIN))
+    else {
+        return writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\"/>");
+    };
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\" \
+        font-family=\"sans-serif\" font-size=\"12\">",
+        bounds.min.x,
+        bounds.min.y,
+        bounds.width(),
+        bounds.height(),
+        bounds.width(),
+        bounds.height()
+    )?;
+    writeln!(
+        out,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>",
+        bounds.min.x,
+        bounds.min.y,
+        bounds.width(),
+        bounds.height()
+    )?;
+
+    // Where each port is drawn
+    let mut outputs = std::collections::HashMap::new();
+    let mut inputs = std::collections::HashMap::new();
+    for (node, pos) in &placed {
+        for (i, port) in node.outputs.iter().enumerate() {
+            outputs.insert(port.id, egui::pos2(pos.x + NODE_WIDTH, port_y(*pos, i)));
+        }
+        for (i, port) in node.inputs.iter().enumerate() {
+            inputs.insert(port.id, egui::pos2(pos.x, port_y(*pos, i)));
+        }
+    }
+
+    for (node, pos) in &placed {
+        let height = node_height(node);
+
+        writeln!(out, "<g>")?;
+        if props {
+            writeln!(
+                out,
+                "<title>{}</title>",
+                escape_xml(&self::props(node).join("\n"))
+            )?;
+        }
+        writeln!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{NODE_WIDTH}\" height=\"{height}\" rx=\"6\" \
+            fill=\"#f4f4f4\" stroke=\"#606060\"/>",
+            pos.x, pos.y
+        )?;
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" font-weight=\"bold\">{}</text>",
+            pos.x + 10.,
+            pos.y + HEADER_HEIGHT * 0.65,
+            escape_xml(&title(node))
+        )?;
+
+        for (ports, x, anchor) in [
+            (&node.inputs, pos.x, "start"),
+            (&node.outputs, pos.x + NODE_WIDTH, "end"),
+        ] {
+            let text_x = if anchor == "start" { x + 10. } else { x - 10. };
+
+            for (i, port) in ports.iter().enumerate() {
+                let y = port_y(*pos, i);
+                writeln!(
+                    out,
+                    "<circle cx=\"{x}\" cy=\"{y}\" r=\"5\" fill=\"{}\" stroke=\"#303030\"/>",
+                    hex(port.color())
+                )?;
+                writeln!(
+                    out,
+                    "<text x=\"{text_x}\" y=\"{}\" text-anchor=\"{anchor}\">{}</text>",
+                    y + 4.,
+                    escape_xml(&port.name)
+                )?;
+            }
+        }
+        writeln!(out, "</g>")?;
+    }
+
+    for link in links {
+        let (_, output_port, _, input_port) = link.ends;
+        let (Some(from), Some(to)) = (outputs.get(&output_port), inputs.get(&input_port)) else {
+            continue;
+        };
+
+        let bend = ((to.x - from.x).abs() / 2.).max(50.);
+        let label = ["State", "Format"]
+            .into_iter()
+            .filter_map(|key| info(link, key))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(
+            out,
+            "<path d=\"M {} {} C {} {}, {} {}, {} {}\" fill=\"none\" stroke=\"#404040\" stroke-width=\"2\">\
+            <title>{}</title></path>",
+            from.x,
+            from.y,
+            from.x + bend,
+            from.y,
+            to.x - bend,
+            to.y,
+            to.x,
+            to.y,
+            escape_xml(&label)
+        )?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_escaping() {
+        assert_eq!(escape_record("in {FL|FR} <x>"), "in \\{FL\\|FR\\} \\<x\\>");
+        assert_eq!(escape_record("a \"b\" \\c"), "a \\\"b\\\" \\\\c");
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::BTreeSet;
+
+use eframe::egui;
+use pipewire::spa::param::format::MediaType;
+
+use crate::ui::{globals_store::ObjectData, util::uis::KvMatcher};
+
+use super::Node;
+
+/// What kind of media a node handles
+#[derive(Clone, Copy)]
+pub enum MediaKind {
+    Audio,
+    Video,
+    Midi,
+    Control,
+    Other,
+}
+
+impl MediaKind {
+    const fn flag(self) -> u8 {
+        match self {
+            Self::Audio => 1 << 0,
+            Self::Video => 1 << 1,
+            Self::Midi => 1 << 2,
+            Self::Control => 1 << 3,
+            Self::Other => 1 << 4,
+        }
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Audio => "Audio",
+            Self::Video => "Video",
+            Self::Midi => "MIDI",
+            Self::Control => "Control",
+            Self::Other => "Other",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        if s.contains("Midi") || s.contains("midi") || s.contains("UMP") {
+            Some(Self::Midi)
+        } else if s.contains("Video") || s.contains("video") {
+            Some(Self::Video)
+        } else if s.contains("Audio") || s.contains("audio") {
+            Some(Self::Audio)
+        } else {
+            None
+        }
+    }
+
+    /// Guessed from the media class of the node, or the formats of its ports
+    pub fn of(node: &Node) -> Self {
+        if let Some(kind) = node
+            .global
+            .borrow()
+            .props()
+            .get("media.class")
+            .and_then(|class| Self::from_str(class))
+        {
+            return kind;
+        }
+
+        for port in node.inputs.iter().chain(&node.outputs) {
+            let port = port.global.borrow();
+
+            if let Some(kind) = port
+                .props()
+                .get("format.dsp")
+                .and_then(|dsp| Self::from_str(dsp))
+            {
+                return kind;
+            }
+
+            match port.object_data() {
+                ObjectData::Port(MediaType::Audio) => return Self::Audio,
+                ObjectData::Port(MediaType::Video) => return Self::Video,
+                ObjectData::Port(MediaType::Application) => return Self::Control,
+                _ => {}
+            }
+        }
+
+        Self::Other
+    }
+}
+
+/// Which nodes the graph shows
+pub struct Filter {
+    properties: KvMatcher,
+    shown_media: u8,
+    hide_idle: bool,
+    hide_unconnected: bool,
+    /// Names of the nodes the user hid
+    hidden_names: BTreeSet<String>,
+}
+
+impl Filter {
+    pub const fn new() -> Self {
+        Self {
+            properties: KvMatcher::new(),
+            shown_media: u8::MAX,
+            hide_idle: false,
+            hide_unconnected: false,
+            hidden_names: BTreeSet::new(),
+        }
+    }
+
+    pub fn hide_name(&mut self, name: String) {
+        self.hidden_names.insert(name);
+    }
+
+    /// Whether the node should be hidden
+    pub fn hides(&self, node: &Node, connected: bool) -> bool {
+        if self.hide_unconnected && !connected {
+            return true;
+        }
+
+        if self.shown_media & MediaKind::of(node).flag() == 0 {
+            return true;
+        }
+
+        let global = node.global.borrow();
+
+        if global
+            .props()
+            .get("node.name")
+            .is_some_and(|name| self.hidden_names.contains(name))
+        {
+            return true;
+        }
+
+        if self.hide_idle
+            && global.info().is_some_and(|info| {
+                info.iter()
+                    .any(|(k, v)| *k == "State" && (v == "Idle" || v == "Suspended"))
+            })
+        {
+            return true;
+        }
+
+        !self.properties.matches(&global.props().iter())
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Media");
+            for kind in [
+                MediaKind::Audio,
+                MediaKind::Video,
+                MediaKind::Midi,
+                MediaKind::Control,
+                MediaKind::Other,
+            ] {
+                if ui
+                    .selectable_label(self.shown_media & kind.flag() != 0, kind.as_str())
+                    .clicked()
+                {
+                    self.shown_media ^= kind.flag();
+                }
+            }
+        });
+
+        ui.checkbox(&mut self.hide_idle, "Hide idle and suspended nodes");
+        ui.checkbox(&mut self.hide_unconnected, "Hide unconnected nodes");
+
+        ui.label("Properties")
+            .on_hover_text("Only nodes with properties that match the below filters will be shown");
+        self.properties.show(ui);
+
+        if !self.hidden_names.is_empty() {
+            ui.separator();
+            ui.label("Hidden nodes")
+                .on_hover_text("Nodes hidden from their headers. Click to show them again.");
+
+            self.hidden_names
+                .retain(|name| !ui.small_button(format!("👁 {name}")).clicked());
+        }
+    }
+}
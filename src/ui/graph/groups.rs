@@ -0,0 +1,60 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Nodes that share a parent, which is their device or else their client
+
+use eframe::egui;
+
+use crate::ui::globals_store::Global;
+
+/// What a collapsed group is drawn as
+pub struct Summary {
+    /// Global ID of the device or client of the group
+    pub parent: u32,
+    pub title: String,
+    /// Names of the member nodes
+    pub members: Vec<String>,
+    /// IDs of the ports of the members, to know when the summary is outdated
+    pub ports: Vec<u32>,
+}
+
+/// Title of the group of a member node
+pub fn title(member: &Global, parent: u32) -> String {
+    let props = member.props();
+
+    if props.contains_key("device.id") {
+        ["device.description", "alsa.card_name", "api.alsa.card.name"]
+            .into_iter()
+            .find_map(|key| props.get(key))
+            .map_or_else(
+                || format!("Device {parent}"),
+                |name| format!("{name} ({parent})"),
+            )
+    } else {
+        props.get("application.name").map_or_else(
+            || format!("Client {parent}"),
+            |name| format!("{name} ({parent})"),
+        )
+    }
+}
+
+/// A distinct color for every group
+#[allow(clippy::cast_precision_loss)]
+pub fn color(parent: u32) -> egui::Color32 {
+    // Golden ratio spacing of hues so that consecutive IDs are far apart
+    let hue = (parent as f32 * 0.618_034).fract();
+    egui::ecolor::Hsva::new(hue, 0.6, 0.8, 1.).into()
+}
@@ -0,0 +1,314 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Layered (Sugiyama-style) drawing of directed graphs.
+//!
+//! 1. Cycles are broken by reversing the edges that go back in a depth first search
+//! 2. Nodes are assigned to layers by their longest path from a source
+//! 3. Edges that span more than one layer are split with dummy nodes
+//! 4. Nodes are ordered within their layers by the barycenters of their neighbors,
+//!    sweeping back and forth and keeping the order with the fewest crossings
+
+#![allow(clippy::cast_precision_loss)]
+
+use std::collections::BTreeSet;
+
+/// Number of down and up sweeps of the crossing minimization
+const SWEEPS: usize = 12;
+
+/// Layers of the drawing from left to right, each with its nodes in order.
+/// [`None`] are dummy nodes that edges spanning multiple layers pass through.
+pub type Layers = Vec<Vec<Option<usize>>>;
+
+/// Lays out the nodes `0..n` connected by the directed `edges`
+pub fn layers(n: usize, edges: &[(usize, usize)]) -> Layers {
+    let edges: BTreeSet<(usize, usize)> = edges
+        .iter()
+        .copied()
+        .filter(|&(from, to)| from != to && from < n && to < n)
+        .collect();
+
+    let edges = break_cycles(n, &edges);
+    let mut layer_of = assign_layers(n, &edges);
+
+    // Split long edges so every edge connects adjacent layers
+    let mut is_dummy = vec![false; n];
+    let mut succs: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+
+    for (from, to) in edges {
+        let mut prev = from;
+        for layer in layer_of[from] + 1..layer_of[to] {
+            let dummy = layer_of.len();
+            layer_of.push(layer);
+            is_dummy.push(true);
+            succs.push(Vec::new());
+            preds.push(Vec::new());
+
+            succs[prev].push(dummy);
+            preds[dummy].push(prev);
+            prev = dummy;
+        }
+        succs[prev].push(to);
+        preds[to].push(prev);
+    }
+
+    let n_layers = layer_of.iter().max().map_or(0, |max| max + 1);
+    let mut order: Vec<Vec<usize>> = vec![Vec::new(); n_layers];
+    for (node, &layer) in layer_of.iter().enumerate() {
+        order[layer].push(node);
+    }
+
+    let order = minimize_crossings(order, &succs, &preds);
+
+    order
+        .into_iter()
+        .map(|layer| {
+            layer
+                .into_iter()
+                .map(|node| (!is_dummy[node]).then_some(node))
+                .collect()
+        })
+        .collect()
+}
+
+/// Reverses the edges that close cycles
+fn break_cycles(n: usize, edges: &BTreeSet<(usize, usize)>) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Unvisited,
+        Visiting,
+        Done,
+    }
+
+    let mut succs = vec![Vec::new(); n];
+    let mut has_preds = vec![false; n];
+    for &(from, to) in edges {
+        succs[from].push(to);
+        has_preds[to] = true;
+    }
+
+    let mut state = vec![State::Unvisited; n];
+    let mut reversed = BTreeSet::new();
+
+    // Start from the sources so that the edges that get reversed are the feedback ones
+    let roots = (0..n)
+        .filter(|&node| !has_preds[node])
+        .chain(0..n)
+        .collect::<Vec<_>>();
+
+    for root in roots {
+        if state[root] != State::Unvisited {
+            continue;
+        }
+
+        state[root] = State::Visiting;
+        let mut stack = vec![(root, 0)];
+
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+
+            if let Some(&succ) = succs[node].get(*next) {
+                *next += 1;
+
+                match state[succ] {
+                    State::Unvisited => {
+                        state[succ] = State::Visiting;
+                        stack.push((succ, 0));
+                    }
+                    State::Visiting => {
+                        reversed.insert((node, succ));
+                    }
+                    State::Done => {}
+                }
+            } else {
+                state[node] = State::Done;
+                stack.pop();
+            }
+        }
+    }
+
+    edges
+        .iter()
+        .map(|&(from, to)| {
+            if reversed.contains(&(from, to)) {
+                (to, from)
+            } else {
+                (from, to)
+            }
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Layer of each node, which is the length of the longest path to it from a source
+fn assign_layers(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut succs = vec![Vec::new(); n];
+    let mut in_degree = vec![0; n];
+    for &(from, to) in edges {
+        succs[from].push(to);
+        in_degree[to] += 1;
+    }
+
+    let mut layer = vec![0; n];
+    let mut ready: Vec<usize> = (0..n).filter(|&node| in_degree[node] == 0).collect();
+
+    while let Some(node) = ready.pop() {
+        for &succ in &succs[node] {
+            layer[succ] = layer[succ].max(layer[node] + 1);
+
+            in_degree[succ] -= 1;
+            if in_degree[succ] == 0 {
+                ready.push(succ);
+            }
+        }
+    }
+
+    layer
+}
+
+fn minimize_crossings(
+    mut order: Vec<Vec<usize>>,
+    succs: &[Vec<usize>],
+    preds: &[Vec<usize>],
+) -> Vec<Vec<usize>> {
+    let mut position = vec![0; succs.len()];
+    for layer in &order {
+        for (i, &node) in layer.iter().enumerate() {
+            position[node] = i;
+        }
+    }
+
+    let mut best = order.clone();
+    let mut best_crossings = crossings(&order, succs, &position);
+
+    for sweep in 0..SWEEPS {
+        let down = sweep % 2 == 0;
+
+        let layers: Vec<usize> = if down {
+            (1..order.len()).collect()
+        } else {
+            (0..order.len().saturating_sub(1)).rev().collect()
+        };
+
+        for layer in layers {
+            let neighbors = if down { preds } else { succs };
+
+            let mut keyed: Vec<(f64, usize)> = order[layer]
+                .iter()
+                .map(|&node| {
+                    let adjacent = &neighbors[node];
+                    let key = if adjacent.is_empty() {
+                        // Keep nodes without neighbors where they are
+                        position[node] as f64
+                    } else {
+                        adjacent.iter().map(|&a| position[a] as f64).sum::<f64>()
+                            / adjacent.len() as f64
+                    };
+                    (key, node)
+                })
+                .collect();
+
+            // Stable so that ties keep their current order
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            order[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+            for (i, &node) in order[layer].iter().enumerate() {
+                position[node] = i;
+            }
+        }
+
+        let c = crossings(&order, succs, &position);
+        if c < best_crossings {
+            best_crossings = c;
+            best.clone_from(&order);
+        }
+    }
+
+    best
+}
+
+/// Number of pairs of edges that cross between adjacent layers
+fn crossings(order: &[Vec<usize>], succs: &[Vec<usize>], position: &[usize]) -> usize {
+    let mut total = 0;
+
+    for layer in order {
+        let mut edges: Vec<(usize, usize)> = layer
+            .iter()
+            .flat_map(|&node| {
+                succs[node]
+                    .iter()
+                    .map(move |&s| (position[node], position[s]))
+            })
+            .collect();
+        edges.sort_unstable();
+
+        for (i, a) in edges.iter().enumerate() {
+            total += edges[i + 1..]
+                .iter()
+                .filter(|b| a.0 < b.0 && a.1 > b.1)
+                .count();
+        }
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layer_of(layers: &Layers, node: usize) -> usize {
+        layers
+            .iter()
+            .position(|layer| layer.contains(&Some(node)))
+            .unwrap()
+    }
+
+    #[test]
+    fn chain_with_feedback() {
+        // 0 -> 1 -> 2 -> 3, 0 -> 3, and 3 -> 1 closing a cycle
+        let layers = layers(4, &[(0, 1), (1, 2), (2, 3), (0, 3), (3, 1)]);
+
+        assert_eq!(layer_of(&layers, 0), 0);
+        assert!(layer_of(&layers, 1) < layer_of(&layers, 2));
+        assert!(layer_of(&layers, 2) < layer_of(&layers, 3));
+
+        // The long edges, including the reversed 3 -> 1, pass through dummies
+        assert_eq!(layers.iter().flatten().filter(|n| n.is_none()).count(), 3);
+    }
+
+    #[test]
+    fn uncrosses() {
+        // Two sources each feeding the sink placed opposite of them
+        let layers = layers(4, &[(0, 3), (1, 2)]);
+
+        let succs = vec![vec![3], vec![2], vec![], vec![]];
+        let mut position = vec![0; 4];
+        let order: Vec<Vec<usize>> = layers
+            .iter()
+            .map(|layer| layer.iter().map(|n| n.unwrap()).collect())
+            .collect();
+        for layer in &order {
+            for (i, &node) in layer.iter().enumerate() {
+                position[node] = i;
+            }
+        }
+
+        assert_eq!(crossings(&order, &succs, &position), 0);
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Level meters of the ports of nodes, fed by capture streams of the backend
+
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+use crate::{backend::Level, ui::globals_store::Global};
+
+/// Lowest level shown
+const FLOOR_DB: f32 = -60.;
+
+/// Levels older than this are of nodes that stopped running
+const STALE: Duration = Duration::from_millis(500);
+
+/// How much the held peaks fall with each update
+const PEAK_FALL: f32 = 0.95;
+
+/// Whether the levels of the node can be captured.
+/// Input streams only have inputs and, unlike sinks, no monitor that carries them.
+pub fn supported(node: &Global) -> bool {
+    !node
+        .props()
+        .get("media.class")
+        .is_some_and(|class| class.starts_with("Stream/Input"))
+}
+
+pub struct Meter {
+    /// Whether the levels are of the inputs of the node, otherwise they are of its outputs
+    pub inputs: bool,
+    levels: Vec<Level>,
+    held_peaks: Vec<f32>,
+    updated: Instant,
+}
+
+impl Meter {
+    /// The meter of the node, with the properties of the stream that captures from it
+    pub fn new(node: &Global) -> (Self, Vec<(String, String)>) {
+        let props = node.props();
+
+        let target = props
+            .get("object.serial")
+            .or_else(|| props.get("node.name"))
+            .cloned()
+            .unwrap_or_else(|| node.id().to_string());
+
+        let class = props.get("media.class").map_or("", String::as_str);
+
+        let mut stream_props = vec![("target.object".to_owned(), target)];
+
+        // Sinks only have inputs, their monitors carry what they receive
+        let inputs = class.contains("Sink");
+        if inputs {
+            stream_props.push(("stream.capture.sink".to_owned(), "true".to_owned()));
+        } else if class.starts_with("Stream/Output") {
+            stream_props.push(("stream.monitor".to_owned(), "true".to_owned()));
+        }
+
+        (
+            Self {
+                inputs,
+                levels: Vec::new(),
+                held_peaks: Vec::new(),
+                updated: Instant::now(),
+            },
+            stream_props,
+        )
+    }
+
+    pub fn set_levels(&mut self, levels: Vec<Level>) {
+        self.held_peaks.resize(levels.len(), 0.);
+        for (held, level) in self.held_peaks.iter_mut().zip(&levels) {
+            *held = level.peak.max(*held * PEAK_FALL);
+        }
+
+        self.levels = levels;
+        self.updated = Instant::now();
+    }
+
+    /// Levels and held peaks of each channel, if they are recent
+    pub fn channels(&self) -> impl Iterator<Item = (Level, f32)> + '_ {
+        let recent = self.updated.elapsed() < STALE;
+
+        self.levels
+            .iter()
+            .zip(&self.held_peaks)
+            .filter(move |_| recent)
+            .map(|(&level, &held)| (level, held))
+    }
+}
+
+fn db(linear: f32) -> f32 {
+    20. * linear.max(f32::MIN_POSITIVE).log10()
+}
+
+/// Draws the RMS as a bar with the held peak as a line over it
+pub fn show(ui: &mut egui::Ui, (level, held_peak): (Level, f32)) {
+    let fraction = |linear| ((db(linear) - FLOOR_DB) / -FLOOR_DB).clamp(0., 1.);
+
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(50., 8.), egui::Sense::hover());
+
+    let painter = ui.painter();
+    painter.rect_filled(rect, 1., ui.visuals().extreme_bg_color);
+
+    let clipping = held_peak >= 1.;
+    let color = if clipping {
+        ui.visuals().error_fg_color
+    } else {
+        egui::Color32::from_rgb(80, 200, 120)
+    };
+
+    let mut rms = rect;
+    rms.set_width(rect.width() * fraction(level.rms));
+    painter.rect_filled(rms, 1., color.gamma_multiply(0.8));
+
+    let peak_x = rect.left() + rect.width() * fraction(held_peak);
+    painter.vline(
+        peak_x,
+        rect.y_range(),
+        egui::Stroke::new(1.5, ui.visuals().strong_text_color()),
+    );
+
+    response.on_hover_text(format!(
+        "Peak {:.1} dB\nRMS {:.1} dB{}",
+        db(level.peak),
+        db(level.rms),
+        if clipping { "\nClipping" } else { "" }
+    ));
+}
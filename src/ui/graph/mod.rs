@@ -0,0 +1,2159 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    rc::Rc,
+};
+
+use eframe::egui;
+use egui::emath::TSTransform;
+use egui_snarl::{
+    InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
+    ui::{PinInfo, SnarlPin, SnarlStyle, WireLayer},
+};
+use pipewire::{spa::param::format::MediaType, types::ObjectType};
+
+mod connect;
+mod export;
+mod filter;
+mod groups;
+mod layout;
+mod meters;
+mod search;
+mod wires;
+
+use crate::{
+    backend::{self, Request},
+    ui::{
+        globals_store::{Global, ObjectData},
+        util::persistence::PersistentView,
+    },
+};
+
+#[derive(Clone)]
+struct Port {
+    id: u32,
+    name: String,
+    global: Rc<RefCell<Global>>,
+}
+
+impl Port {
+    fn color(&self) -> egui::Color32 {
+        let global = self.global.borrow();
+
+        let media_type = match global.object_data() {
+            ObjectData::Port(media_type) => Some(media_type),
+            ObjectData::Other(ObjectType::Port) => None, // This is in case the media type has not be sent by PipeWire yet
+            _ => panic!("Global referenced by a graph pin should be a port"),
+        };
+
+        media_type.map_or(egui::Color32::GRAY, |media_type| match *media_type {
+            MediaType::Audio => egui::Color32::BLUE,
+            MediaType::Video => egui::Color32::YELLOW,
+            MediaType::Application => egui::Color32::RED,
+            MediaType::Binary => egui::Color32::GREEN,
+            MediaType::Image => egui::Color32::ORANGE,
+            _ => egui::Color32::GRAY,
+        })
+    }
+
+    fn snarl_pin_info(&self) -> PinInfo {
+        PinInfo::circle().with_fill(self.color())
+    }
+}
+
+struct Node {
+    user_label: String,
+    initial_auto_pos: Option<egui::Pos2>,
+    inputs: Vec<Port>,
+    outputs: Vec<Port>,
+    resize: bool,
+    global: Rc<RefCell<Global>>,
+    /// Set if this node stands in for a collapsed group
+    summary: Option<groups::Summary>,
+}
+
+impl Node {
+    /// Rough height of the node when drawn, for laying out nodes before they are drawn
+    #[allow(clippy::cast_precision_loss)]
+    fn estimated_height(&self, open: bool) -> f32 {
+        let ports = self.inputs.len().max(self.outputs.len()) as f32;
+        let details = if open { 250. } else { 0. };
+
+        50. + 25. * ports + details
+    }
+
+    fn new(global: Rc<RefCell<Global>>) -> Self {
+        let name = global.borrow().name().cloned().unwrap_or_default();
+        Self {
+            user_label: name,
+            initial_auto_pos: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            resize: false,
+            global,
+            summary: None,
+        }
+    }
+}
+
+fn create_link(sx: &backend::Sender, output_port: u32, input_port: u32) {
+    sx.send(Request::CreateObject(
+        ObjectType::Link,
+        "link-factory".to_owned(),
+        vec![
+            ("link.output.port".to_owned(), output_port.to_string()),
+            ("link.input.port".to_owned(), input_port.to_string()),
+            ("object.linger".to_owned(), "true".to_owned()),
+        ],
+    ))
+    .ok();
+}
+
+fn hidden_links_marker(ui: &mut egui::Ui, hidden: Option<&Vec<String>>) {
+    if let Some(hidden) = hidden {
+        ui.weak("⋯")
+            .on_hover_text(format!("Linked to hidden nodes:\n{}", hidden.join("\n")));
+    }
+}
+
+struct Viewer<'a, 'b> {
+    sx: &'a backend::Sender,
+    wires: &'b HashMap<(OutPinId, InPinId), u32>,
+    hidden_links: &'b HashMap<u32, Vec<String>>,
+    port_links: &'b HashMap<u32, wires::PortLinks>,
+    pin_positions: Rc<RefCell<wires::PinPositions>>,
+    port_levels: &'b HashMap<u32, (backend::Level, f32)>,
+    meters: &'b HashMap<u32, meters::Meter>,
+    toggle_meter: Option<u32>,
+    connecting_from: Option<u32>,
+    connect: Option<u32>,
+    hide: Option<String>,
+    highlighted: Option<u32>,
+    expand: Option<u32>,
+    rects: HashMap<NodeId, egui::Rect>,
+    layer: Option<egui::LayerId>,
+    transform: Option<TSTransform>,
+}
+
+impl egui_snarl::ui::SnarlViewer<Node> for Viewer<'_, '_> {
+    fn title(&mut self, _: &Node) -> String {
+        String::new()
+    }
+
+    fn show_header(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut egui::Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Node>,
+    ) {
+        self.rects.insert(node, ui.max_rect());
+        self.layer = Some(ui.layer_id());
+
+        let node = snarl
+            .get_node_mut(node)
+            .expect("snarl requested header of non-existent node");
+
+        if let Some(summary) = &node.summary {
+            if ui
+                .small_button("⏷")
+                .on_hover_text("Expand the group")
+                .clicked()
+            {
+                self.expand = Some(summary.parent);
+            }
+
+            ui.strong(&summary.title);
+
+            return;
+        }
+
+        let id = node.global.borrow().id();
+
+        if self.highlighted == Some(id) {
+            ui.painter().rect_stroke(
+                ui.max_rect().expand(4.),
+                4.,
+                egui::Stroke::new(3., ui.visuals().selection.stroke.color),
+                egui::StrokeKind::Outside,
+            );
+        }
+
+        ui.label(id.to_string());
+
+        if matches!(filter::MediaKind::of(node), filter::MediaKind::Audio)
+            && meters::supported(&node.global.borrow())
+            && ui
+                .selectable_label(self.meters.contains_key(&id), "📶")
+                .on_hover_text("Show the levels of the ports")
+                .clicked()
+        {
+            self.toggle_meter = Some(id);
+        }
+
+        match self.connecting_from {
+            None if !node.outputs.is_empty() => {
+                if ui
+                    .small_button("🔗")
+                    .on_hover_text("Connect this node to another, pairing their ports by channel")
+                    .clicked()
+                {
+                    self.connect = Some(id);
+                }
+            }
+            Some(from) if from == id => {
+                if ui
+                    .selectable_label(true, "🔗")
+                    .on_hover_text("Stop connecting")
+                    .clicked()
+                {
+                    self.connect = Some(id);
+                }
+            }
+            Some(_) if !node.inputs.is_empty() => {
+                if ui.small_button("⮕").on_hover_text("Connect here").clicked() {
+                    self.connect = Some(id);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(name) = node.global.borrow().props().get("node.name")
+            && ui
+                .small_button("👁")
+                .on_hover_text("Hide this node. It can be shown again from the graph filters.")
+                .clicked()
+        {
+            self.hide = Some(name.clone());
+        }
+
+        node.resize = egui::TextEdit::singleline(&mut node.user_label)
+            .desired_width(0.0)
+            .clip_text(false)
+            .show(ui)
+            .response
+            .changed();
+    }
+
+    fn has_footer(&mut self, _: &Node) -> bool {
+        true
+    }
+
+    fn outputs(&mut self, node: &Node) -> usize {
+        node.outputs.len()
+    }
+
+    fn inputs(&mut self, node: &Node) -> usize {
+        node.inputs.len()
+    }
+
+    fn current_transform(
+        &mut self,
+        trasnform: &mut egui::emath::TSTransform,
+        _snarl: &mut Snarl<Node>,
+    ) {
+        if let Some(initial_transform) = self.transform.take() {
+            *trasnform = initial_transform;
+        } else {
+            self.transform = Some(*trasnform);
+        }
+    }
+
+    fn show_footer(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut egui::Ui,
+        scale: f32,
+        snarl: &mut Snarl<Node>,
+    ) {
+        if !snarl
+            .get_node_info(node)
+            .expect("snarl requested footer of non-existent node")
+            .open
+        {
+            return;
+        }
+
+        let node_id = node;
+        let node = snarl.get_node_mut(node).unwrap();
+
+        if let Some(summary) = &node.summary {
+            ui.label(format!("{} nodes", summary.members.len()));
+            for name in &summary.members {
+                ui.weak(name);
+            }
+
+            if let Some(rect) = self.rects.get_mut(&node_id) {
+                *rect = rect.union(ui.min_rect());
+            }
+
+            return;
+        }
+
+        // The global should expand to fill the space around it if the snarl frame
+        // is wide enough. This can be done by making the global justified, which
+        // causes this problem:
+        // 1. Node and global are drawn
+        // 2. Something in the global is expanded, causing the node to widen
+        // 3. Expanded elements are collapsed
+        // 4. Global stays wide, making the node stay wide, wasting space
+        //
+        // To solve this, store the minimum width required by the global and the node.
+        // If the global width is less than the node width, the global should expand
+        // to exactly the width of the node
+
+        let min_global_width_key = ui.id().with("min_global_width");
+        let min_node_width_key = ui.id().with("min_node_width");
+
+        if node.resize {
+            // Redo size calculations
+            ui.data_mut(|d| {
+                d.remove_temp::<f32>(min_global_width_key);
+                d.remove_temp::<f32>(min_node_width_key);
+            });
+
+            node.resize = false;
+        }
+
+        let min_global_width: Option<f32> = ui.data(|d| d.get_temp(min_global_width_key));
+        let current_node_width = ui.max_rect().width() / scale;
+
+        let global_width = egui::CollapsingHeader::new("Details")
+            .default_open(true)
+            .show_unindented(ui, |ui| {
+                let mut ui_builder = egui::UiBuilder::new();
+
+                // Only after the global has been drawn we can know the final node width
+                let min_node_width = if min_global_width.is_some() {
+                    ui.data_mut(|d| {
+                        let min_node_width =
+                            d.get_temp_mut_or(min_node_width_key, current_node_width);
+                        Some(*min_node_width)
+                    })
+                } else {
+                    ui_builder.sizing_pass = true;
+                    ui.ctx().request_discard(format!(
+                        "graph node {} sizing",
+                        node.global.borrow().id()
+                    ));
+                    None
+                };
+
+                ui.scope_builder(ui_builder, |ui| {
+                    egui::ScrollArea::vertical()
+                        .min_scrolled_height(450. * scale)
+                        .max_height(450. * scale)
+                        .show(ui, |ui| {
+                            let mut layout = egui::Layout::top_down(egui::Align::Min);
+
+                            if !ui.is_sizing_pass() {
+                                // The part of the node drawn by snarl includes the port names which may make
+                                // it wider than the width of the global. If that's the case, the global
+                                // should expand to fill the unused space. If that is not the case the global
+                                // should stay as narrow as possible and expand the outer UI only when needed
+                                if let Some((min_node_width, _)) =
+                                    Option::zip(min_node_width, min_global_width)
+                                        .filter(|&(n, g)| n > g)
+                                {
+                                    layout.cross_justify = true;
+                                    ui.set_max_width(min_node_width * scale);
+                                } else {
+                                    ui.set_max_width(450. * scale);
+                                }
+                            }
+
+                            ui.with_layout(layout, |ui| {
+                                // Text should wrap when sizing. Otherwise the following is possible
+                                // 1. Node appears and some collapsing header is open, showing some wide text
+                                // 2. The wide text causes the calculated width to be larger than needed
+                                // 3. The collapsing header is closed, but because of 2. the node stays wide
+                                if !ui.is_sizing_pass() {
+                                    ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+                                }
+                                node.global.borrow_mut().show(ui, true, self.sx);
+                            });
+                        });
+                })
+                .response
+                .rect
+                .width()
+                    / scale
+            })
+            .body_returned;
+
+        if let Some(global_width) = global_width
+            && min_global_width.is_none()
+        {
+            ui.data_mut(|d| {
+                d.insert_temp(min_global_width_key, global_width);
+            });
+        }
+
+        if let Some(rect) = self.rects.get_mut(&node_id) {
+            *rect = rect.union(ui.min_rect());
+        }
+    }
+
+    fn show_input(
+        &mut self,
+        pin: &InPin,
+        ui: &mut egui::Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Node>,
+    ) -> impl SnarlPin + 'static {
+        let node = snarl
+            .get_node(pin.id.node)
+            .expect("snarl requested showing of pin not belonging to any node");
+
+        let port = &node.inputs[pin.id.input];
+        let links = self.port_links.get(&port.id);
+
+        hidden_links_marker(ui, self.hidden_links.get(&port.id));
+
+        let name = snarl
+            .get_node_info(pin.id.node)
+            .unwrap()
+            .open
+            .then(|| ui.label(&port.name));
+
+        if let Some(links) = links {
+            links.show(ui, name);
+        }
+
+        if let Some(&level) = self.port_levels.get(&port.id) {
+            meters::show(ui, level);
+        }
+
+        wires::Pin::input(
+            pin.id,
+            port.snarl_pin_info(),
+            links.map(|_| port.color()),
+            &self.pin_positions,
+        )
+    }
+
+    fn show_output(
+        &mut self,
+        pin: &OutPin,
+        ui: &mut egui::Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Node>,
+    ) -> impl SnarlPin + 'static {
+        let node = snarl
+            .get_node(pin.id.node)
+            .expect("snarl requested showing of pin not belonging to any node");
+
+        let port = &node.outputs[pin.id.output];
+        let links = self.port_links.get(&port.id);
+
+        let name = snarl
+            .get_node_info(pin.id.node)
+            .unwrap()
+            .open
+            .then(|| ui.label(&port.name));
+
+        if let Some(&level) = self.port_levels.get(&port.id) {
+            meters::show(ui, level);
+        }
+
+        if let Some(links) = links {
+            links.show(ui, name);
+        }
+
+        hidden_links_marker(ui, self.hidden_links.get(&port.id));
+
+        wires::Pin::output(
+            pin.id,
+            port.snarl_pin_info(),
+            links.map(|_| port.color()),
+            &self.pin_positions,
+        )
+    }
+
+    fn connect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<Node>) {
+        let Some(out) = snarl.get_node(from.id.node) else {
+            eprintln!("snarl requested connection from port of non-existent node");
+            return;
+        };
+
+        let Some(inp) = snarl.get_node(to.id.node) else {
+            eprintln!("snarl requested connection to port of non-existent node");
+            return;
+        };
+
+        create_link(
+            self.sx,
+            out.outputs[from.id.output].id,
+            inp.inputs[to.id.input].id,
+        );
+    }
+
+    fn disconnect(&mut self, from: &OutPin, to: &InPin, _snarl: &mut Snarl<Node>) {
+        let Some(&link_id) = self.wires.get(&(from.id, to.id)) else {
+            eprintln!("snarl requested destruction of non-existent link");
+            return;
+        };
+
+        self.sx.send(Request::DestroyObject(link_id)).ok();
+    }
+
+    // Make secondary-clicking on ports do nothing
+    fn drop_inputs(&mut self, _pin: &InPin, _snarl: &mut Snarl<Node>) {}
+    fn drop_outputs(&mut self, _pin: &OutPin, _snarl: &mut Snarl<Node>) {}
+}
+
+// Uses serde's untagged enum repr to deserialize from versions
+// before the `UserNodePosInfo::target_object` field
+#[cfg(feature = "persistence")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum UserNodePosInfoRepr {
+    WithTarget {
+        target_object: Option<String>,
+        pos: egui::Pos2,
+    },
+    Pos {
+        x: f32,
+        y: f32,
+    },
+}
+
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "UserNodePosInfoRepr") // For migration
+)]
+#[derive(Clone)]
+struct UserNodePosInfo {
+    /// Used to tiebreak saved positions of nodes with the same names.
+    target_object: Option<String>,
+    pos: egui::Pos2,
+}
+
+#[cfg(feature = "persistence")]
+impl From<UserNodePosInfoRepr> for UserNodePosInfo {
+    fn from(value: UserNodePosInfoRepr) -> Self {
+        match value {
+            UserNodePosInfoRepr::WithTarget { target_object, pos } => Self { target_object, pos },
+            UserNodePosInfoRepr::Pos { x, y } => Self {
+                target_object: None,
+                pos: egui::Pos2::new(x, y),
+            },
+        }
+    }
+}
+
+struct HiddenNode {
+    node: Node,
+    /// [`None`] if the node was hidden before it got positioned
+    pos: Option<egui::Pos2>,
+    open: bool,
+}
+
+fn port_ids(hidden: &HashMap<u32, HiddenNode>, ids: &[u32]) -> Vec<u32> {
+    let mut ports: Vec<u32> = ids
+        .iter()
+        .filter_map(|id| hidden.get(id))
+        .flat_map(|hidden| {
+            let node = &hidden.node;
+            node.inputs.iter().chain(&node.outputs).map(|port| port.id)
+        })
+        .collect();
+    ports.sort_unstable();
+    ports
+}
+
+/// Output node, output port, input node and input port of a link
+type LinkEnds = (u32, u32, u32, u32);
+
+struct Link {
+    ends: LinkEnds,
+    global: Rc<RefCell<Global>>,
+}
+
+pub struct Graph {
+    snarl: Snarl<Node>,
+    nodes: HashMap<u32, NodeId>,
+    wires: HashMap<(OutPinId, InPinId), u32>,
+    ports: HashSet<u32>,
+
+    /// All links, including those of hidden nodes
+    links: HashMap<u32, Link>,
+    hidden: HashMap<u32, HiddenNode>,
+    filter: filter::Filter,
+
+    collapsed: HashSet<u32>,
+    summaries: HashMap<u32, NodeId>,
+
+    exporter: export::Exporter,
+
+    meters: HashMap<u32, meters::Meter>,
+
+    connecting: Option<connect::Pending>,
+
+    search: String,
+    highlighted: Option<u32>,
+
+    unpositioned: HashSet<NodeId>,
+    user_positions: HashMap<String, VecDeque<UserNodePosInfo>>,
+
+    transform: TSTransform,
+    restored_transform: Option<TSTransform>,
+    /// Layer the nodes were drawn on in the last frame
+    snarl_layer: Option<egui::LayerId>,
+}
+
+impl Graph {
+    /// If target_object is a serial, this will find the name of the node with that serial.
+    /// If target_object is a node name, this just returns it.
+    fn to_target_name<'a>(&self, target_object: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+        let target_object = target_object.into();
+
+        let Some(target_serial) = target_object.parse::<u32>().ok() else {
+            return target_object;
+        };
+
+        self.nodes
+            .iter()
+            .find_map(|(_, &n)| {
+                let node = self
+                    .snarl
+                    .get_node_info(n)
+                    .expect("Registered node has no snarl info")
+                    .value
+                    .global
+                    .borrow();
+                let name = node.props().get("node.name")?;
+
+                let serial = node
+                    .props()
+                    .get("object.serial")
+                    .and_then(|s| s.parse::<u32>().ok());
+
+                if serial.is_some_and(|serial| serial == target_serial) {
+                    Some(Cow::Owned(name.clone()))
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(target_object)
+    }
+
+    fn find_user_position<'a>(
+        &mut self,
+        name: &str,
+        target_object: Option<impl Into<Cow<'a, str>>>,
+    ) -> Option<egui::Pos2> {
+        let target_object = target_object.map(|t| self.to_target_name(t));
+
+        if let Some(positions) = self.user_positions.get_mut(name) {
+            positions
+                .iter()
+                .position(|info| info.target_object.as_deref() == target_object.as_deref())
+                .and_then(|i| positions.remove(i))
+                .or_else(|| positions.pop_back())
+                .map(|i| i.pos)
+        } else {
+            None
+        }
+    }
+
+    fn save_user_position<'a>(
+        &mut self,
+        name: String,
+        target_object: Option<impl Into<Cow<'a, str>>>,
+        pos: egui::Pos2,
+    ) {
+        let target_object = target_object.map(|t| self.to_target_name(t).into_owned());
+
+        self.user_positions
+            .entry(name)
+            .or_default()
+            .push_back(UserNodePosInfo { target_object, pos });
+    }
+
+    pub fn new() -> Self {
+        Self {
+            snarl: Snarl::new(),
+            nodes: HashMap::new(),
+            wires: HashMap::new(),
+            ports: HashSet::new(),
+
+            links: HashMap::new(),
+            hidden: HashMap::new(),
+            filter: filter::Filter::new(),
+
+            collapsed: HashSet::new(),
+            summaries: HashMap::new(),
+
+            exporter: export::Exporter::new(),
+
+            meters: HashMap::new(),
+
+            connecting: None,
+
+            search: String::new(),
+            highlighted: None,
+
+            unpositioned: HashSet::new(),
+            user_positions: HashMap::new(),
+
+            transform: TSTransform::IDENTITY,
+
+            restored_transform: Some(TSTransform::IDENTITY),
+            snarl_layer: None,
+        }
+    }
+
+    fn node_mut(&mut self, id: u32) -> Option<&mut Node> {
+        if let Some(&node_id) = self.nodes.get(&id) {
+            self.snarl.get_node_mut(node_id)
+        } else {
+            self.hidden.get_mut(&id).map(|hidden| &mut hidden.node)
+        }
+    }
+
+    fn node(&self, id: u32) -> Option<&Node> {
+        if let Some(&node_id) = self.nodes.get(&id) {
+            self.snarl.get_node(node_id)
+        } else {
+            self.hidden.get(&id).map(|hidden| &hidden.node)
+        }
+    }
+
+    fn remember_position(&mut self, node: &Node, pos: egui::Pos2) {
+        // Save only if its position was restored or the user moved this
+        if node.initial_auto_pos.is_some_and(|ip| ip == pos) {
+            return;
+        }
+
+        let global_ref = node.global.borrow();
+        let props = global_ref.props();
+
+        if let Some(name) = props.get("node.name").map(ToOwned::to_owned) {
+            let target_object = props.get("target.object").cloned();
+
+            drop(global_ref);
+
+            self.save_user_position(name, target_object, pos);
+        }
+    }
+
+    fn disconnect_node(&mut self, node_id: NodeId) {
+        self.wires.retain(|(out, inp), _| {
+            if out.node == node_id || inp.node == node_id {
+                self.snarl.disconnect(*out, *inp);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// The snarl node that the node with the global ID is drawn as,
+    /// which is the summary of its group if the group is collapsed
+    fn represented(&self, id: u32) -> Option<NodeId> {
+        if let Some(&node_id) = self.nodes.get(&id) {
+            return Some(node_id);
+        }
+
+        let parent = self.hidden.get(&id)?.node.global.borrow().parent_id()?;
+
+        self.summaries.get(&parent).copied()
+    }
+
+    fn hide_node(&mut self, id: u32) {
+        let Some(node_id) = self.nodes.remove(&id) else {
+            return;
+        };
+
+        self.disconnect_node(node_id);
+
+        let Some(info) = self.snarl.get_node_info(node_id) else {
+            return;
+        };
+
+        let pos = (!self.unpositioned.remove(&node_id)).then_some(info.pos);
+        let open = info.open;
+
+        let node = self.snarl.remove_node(node_id);
+
+        self.hidden.insert(id, HiddenNode { node, pos, open });
+    }
+
+    fn unhide_node(&mut self, id: u32) {
+        let Some(HiddenNode { node, pos, open }) = self.hidden.remove(&id) else {
+            return;
+        };
+
+        let node_id = if open {
+            self.snarl
+                .insert_node(pos.unwrap_or(egui::Pos2::ZERO), node)
+        } else {
+            self.snarl
+                .insert_node_collapsed(pos.unwrap_or(egui::Pos2::ZERO), node)
+        };
+
+        self.nodes.insert(id, node_id);
+
+        if pos.is_none() {
+            self.unpositioned.insert(node_id);
+        }
+
+        let links: Vec<(u32, LinkEnds)> = self
+            .links
+            .iter()
+            .map(|(&link, Link { ends, .. })| (link, *ends))
+            .filter(|(_, ends)| ends.0 == id || ends.2 == id)
+            .collect();
+
+        for (link, ends) in links {
+            self.connect_link(link, ends);
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        let mut connected = HashSet::new();
+        for &Link {
+            ends: (output_node, _, input_node, _),
+            ..
+        } in self.links.values()
+        {
+            connected.insert(output_node);
+            connected.insert(input_node);
+        }
+
+        let to_hide: Vec<u32> = self
+            .nodes
+            .iter()
+            .filter(|(id, node_id)| {
+                self.snarl.get_node(**node_id).is_some_and(|node| {
+                    self.in_collapsed_group(node)
+                        || self.filter.hides(node, connected.contains(*id))
+                })
+            })
+            .map(|(&id, _)| id)
+            .collect();
+
+        let to_show: Vec<u32> = self
+            .hidden
+            .iter()
+            .filter(|(id, hidden)| {
+                !self.in_collapsed_group(&hidden.node)
+                    && !self.filter.hides(&hidden.node, connected.contains(*id))
+            })
+            .map(|(&id, _)| id)
+            .collect();
+
+        for id in to_hide {
+            self.hide_node(id);
+        }
+
+        for id in to_show {
+            self.unhide_node(id);
+        }
+
+        self.update_summaries();
+    }
+
+    fn in_collapsed_group(&self, node: &Node) -> bool {
+        node.global
+            .borrow()
+            .parent_id()
+            .is_some_and(|parent| self.collapsed.contains(&parent))
+    }
+
+    /// Draws the members of each collapsed group as a single node with all of their ports
+    fn update_summaries(&mut self) {
+        let mut members: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for (&id, hidden) in &self.hidden {
+            if let Some(parent) = hidden.node.global.borrow().parent_id()
+                && self.collapsed.contains(&parent)
+            {
+                members.entry(parent).or_default().push(id);
+            }
+        }
+
+        // Groups whose members are all gone
+        self.collapsed.retain(|parent| members.contains_key(parent));
+
+        // Remove the summaries of expanded groups and of groups whose ports changed
+        let outdated: Vec<u32> = self
+            .summaries
+            .iter()
+            .filter(|&(parent, &node_id)| {
+                members.get(parent).is_none_or(|ids| {
+                    self.snarl
+                        .get_node(node_id)
+                        .and_then(|node| node.summary.as_ref())
+                        .is_none_or(|summary| summary.ports != port_ids(&self.hidden, ids))
+                })
+            })
+            .map(|(&parent, _)| parent)
+            .collect();
+
+        let mut previous_positions = HashMap::new();
+        for parent in outdated {
+            let Some(node_id) = self.summaries.remove(&parent) else {
+                continue;
+            };
+
+            self.disconnect_node(node_id);
+
+            if let Some(info) = self.snarl.get_node_info(node_id) {
+                previous_positions.insert(parent, info.pos);
+            }
+            self.snarl.remove_node(node_id);
+        }
+
+        for (parent, mut ids) in members {
+            if self.summaries.contains_key(&parent) {
+                continue;
+            }
+
+            ids.sort_unstable();
+
+            let mut inputs = Vec::new();
+            let mut outputs = Vec::new();
+            let mut names = Vec::new();
+            let mut pos: Option<egui::Pos2> = None;
+
+            for id in &ids {
+                let hidden = &self.hidden[id];
+
+                inputs.extend(hidden.node.inputs.iter().cloned());
+                outputs.extend(hidden.node.outputs.iter().cloned());
+                names.push(hidden.node.user_label.clone());
+
+                if let Some(member_pos) = hidden.pos {
+                    pos = Some(pos.map_or(member_pos, |pos| pos.min(member_pos)));
+                }
+            }
+
+            let ports = port_ids(&self.hidden, &ids);
+            let global = Rc::clone(&self.hidden[&ids[0]].node.global);
+            let title = groups::title(&global.borrow(), parent);
+
+            let node = Node {
+                user_label: title.clone(),
+                initial_auto_pos: None,
+                inputs,
+                outputs,
+                resize: false,
+                global,
+                summary: Some(groups::Summary {
+                    parent,
+                    title,
+                    members: names,
+                    ports,
+                }),
+            };
+
+            let pos = previous_positions
+                .get(&parent)
+                .copied()
+                .or(pos)
+                .unwrap_or(egui::Pos2::ZERO);
+
+            self.summaries
+                .insert(parent, self.snarl.insert_node(pos, node));
+
+            let links: Vec<(u32, LinkEnds)> = self
+                .links
+                .iter()
+                .map(|(&link, Link { ends, .. })| (link, *ends))
+                .filter(|(_, ends)| ids.contains(&ends.0) || ids.contains(&ends.2))
+                .collect();
+
+            for (link, ends) in links {
+                self.connect_link(link, ends);
+            }
+        }
+    }
+
+    fn draw_wires(
+        &self,
+        ui: &egui::Ui,
+        pin_positions: &wires::PinPositions,
+        appearances: &HashMap<u32, wires::Appearance>,
+        layer: egui::LayerId,
+    ) {
+        let painter = ui.ctx().layer_painter(layer).with_clip_rect(ui.max_rect());
+
+        for ((output, input), id) in &self.wires {
+            if let Some(appearance) = appearances.get(id)
+                && let Some(&from) = pin_positions.outputs.get(output)
+                && let Some(&to) = pin_positions.inputs.get(input)
+            {
+                appearance.draw(&painter, from, to, self.transform.scaling);
+            }
+        }
+
+        pin_positions.draw_dragged(ui, &painter, self.transform.scaling);
+    }
+
+    fn show_groups(
+        &mut self,
+        ui: &mut egui::Ui,
+        rects: &HashMap<NodeId, egui::Rect>,
+        layer: egui::LayerId,
+        (painter, slot): (egui::Painter, egui::layers::ShapeIdx),
+    ) {
+        const MARGIN: f32 = 10.;
+        const TITLE_HEIGHT: f32 = 24.;
+
+        let mut groups: BTreeMap<u32, Vec<NodeId>> = BTreeMap::new();
+        for &node_id in self.nodes.values() {
+            if let Some(parent) = self
+                .snarl
+                .get_node(node_id)
+                .and_then(|node| node.global.borrow().parent_id())
+            {
+                groups.entry(parent).or_default().push(node_id);
+            }
+        }
+
+        let mut shapes = Vec::new();
+
+        for (parent, members) in groups {
+            if members.len() < 2 {
+                continue;
+            }
+
+            let Some(rect) = members
+                .iter()
+                .filter_map(|node_id| rects.get(node_id))
+                .copied()
+                .reduce(egui::Rect::union)
+            else {
+                continue;
+            };
+
+            let scale = self.transform.scaling;
+            let rect = rect.expand(MARGIN * scale);
+            let title_rect = egui::Rect::from_min_max(
+                rect.left_top() - egui::vec2(0., TITLE_HEIGHT * scale),
+                rect.right_top(),
+            );
+
+            let color = groups::color(parent);
+            let rounding = 6. * scale;
+
+            shapes.push(egui::Shape::Rect(egui::epaint::RectShape::new(
+                rect.union(title_rect),
+                rounding,
+                color.gamma_multiply(0.08),
+                egui::Stroke::new(1.5, color.gamma_multiply(0.6)),
+                egui::StrokeKind::Outside,
+            )));
+            shapes.push(egui::Shape::Rect(egui::epaint::RectShape::filled(
+                title_rect,
+                egui::CornerRadius {
+                    sw: 0,
+                    se: 0,
+                    ..rounding.into()
+                },
+                color.gamma_multiply(0.3),
+            )));
+
+            let Some(title) = self
+                .snarl
+                .get_node(members[0])
+                .map(|node| groups::title(&node.global.borrow(), parent))
+            else {
+                continue;
+            };
+
+            let mut title_ui = ui.new_child(
+                egui::UiBuilder::new()
+                    .id_salt(("group", parent))
+                    .layer_id(layer)
+                    .max_rect(title_rect.shrink2(egui::vec2(6., 2.)))
+                    .layout(egui::Layout::left_to_right(egui::Align::Center)),
+            );
+            title_ui.set_clip_rect(ui.max_rect());
+
+            if title_ui
+                .small_button("⏶")
+                .on_hover_text("Collapse the group into a single node")
+                .clicked()
+            {
+                self.collapsed.insert(parent);
+            }
+
+            let handle = title_ui
+                .add(
+                    egui::Label::new(egui::RichText::new(title).strong())
+                        .selectable(false)
+                        .sense(egui::Sense::drag()),
+                )
+                .on_hover_cursor(egui::CursorIcon::Grab)
+                .on_hover_text("Drag to move the group");
+
+            if handle.dragged() {
+                let delta = handle.drag_delta() / scale;
+
+                for node_id in members {
+                    if let Some(info) = self.snarl.get_node_info_mut(node_id) {
+                        info.pos += delta;
+                    }
+                }
+            }
+        }
+
+        painter.set(slot, shapes);
+    }
+
+    fn hidden_links(&self) -> HashMap<u32, Vec<String>> {
+        let mut hidden_links: HashMap<u32, Vec<String>> = HashMap::new();
+
+        let name = |hidden: &HiddenNode| {
+            let global = hidden.node.global.borrow();
+            global
+                .name()
+                .cloned()
+                .unwrap_or_else(|| global.id().to_string())
+        };
+
+        // Nodes of collapsed groups aren't hidden, they are drawn as their summaries
+        let hidden = |id| {
+            self.hidden
+                .get(&id)
+                .filter(|_| self.represented(id).is_none())
+        };
+
+        for &Link {
+            ends: (output_node, output_port, input_node, input_port),
+            ..
+        } in self.links.values()
+        {
+            match (hidden(output_node), hidden(input_node)) {
+                (Some(output), None) => {
+                    hidden_links
+                        .entry(input_port)
+                        .or_default()
+                        .push(name(output));
+                }
+                (None, Some(input)) => {
+                    hidden_links
+                        .entry(output_port)
+                        .or_default()
+                        .push(name(input));
+                }
+                _ => {}
+            }
+        }
+
+        hidden_links
+    }
+
+    fn link_appearances(&self) -> HashMap<u32, wires::Appearance> {
+        self.wires
+            .values()
+            .filter_map(|id| self.links.get(id))
+            .map(|link| {
+                let global = link.global.borrow();
+                (global.id(), wires::Appearance::of(&global))
+            })
+            .collect()
+    }
+
+    fn port_links(
+        &self,
+        appearances: &HashMap<u32, wires::Appearance>,
+    ) -> HashMap<u32, wires::PortLinks> {
+        let mut port_links: HashMap<u32, wires::PortLinks> = HashMap::new();
+
+        for (id, appearance) in appearances {
+            let Some(&Link {
+                ends: (_, output_port, _, input_port),
+                ..
+            }) = self.links.get(id)
+            else {
+                continue;
+            };
+
+            port_links
+                .entry(output_port)
+                .or_insert_with(wires::PortLinks::new)
+                .push(appearance.clone());
+            port_links
+                .entry(input_port)
+                .or_insert_with(wires::PortLinks::new)
+                .push(appearance.clone());
+        }
+
+        port_links
+    }
+
+    /// Levels of the ports of the metered nodes, with each channel of a node
+    /// going to its ports in the order PipeWire numbers them
+    fn port_levels(&self) -> HashMap<u32, (backend::Level, f32)> {
+        let mut port_levels = HashMap::new();
+
+        for (id, meter) in &self.meters {
+            let Some(node) = self.nodes.get(id).and_then(|&n| self.snarl.get_node(n)) else {
+                continue;
+            };
+
+            let mut ports: Vec<(u32, u32)> = if meter.inputs {
+                &node.inputs
+            } else {
+                &node.outputs
+            }
+            .iter()
+            .map(|port| {
+                let index = port
+                    .global
+                    .borrow()
+                    .props()
+                    .get("port.id")
+                    .and_then(|index| index.parse().ok())
+                    .unwrap_or(port.id);
+                (index, port.id)
+            })
+            .collect();
+            ports.sort_unstable();
+
+            for ((_, port), channel) in ports.into_iter().zip(meter.channels()) {
+                port_levels.insert(port, channel);
+            }
+        }
+
+        port_levels
+    }
+
+    fn toggle_meter(&mut self, id: u32, sx: &backend::Sender) {
+        if self.meters.remove(&id).is_some() {
+            sx.send(Request::StopMeter(id)).ok();
+            return;
+        }
+
+        let Some(node) = self.node_mut(id) else {
+            return;
+        };
+
+        let (meter, props) = meters::Meter::new(&node.global.borrow());
+
+        self.meters.insert(id, meter);
+        sx.send(Request::StartMeter { node: id, props }).ok();
+    }
+
+    pub fn set_levels(&mut self, node: u32, levels: Vec<backend::Level>) {
+        if let Some(meter) = self.meters.get_mut(&node) {
+            meter.set_levels(levels);
+        }
+    }
+
+    fn choose_for_connecting(&mut self, id: u32) {
+        if let Some(pending) = &mut self.connecting
+            && pending.from != id
+        {
+            pending.to = Some(id);
+        } else if self.connecting.is_some() {
+            self.connecting = None;
+        } else {
+            self.connecting = Some(connect::Pending {
+                from: id,
+                to: None,
+                fan: false,
+            });
+        }
+    }
+
+    fn show_connect(&mut self, ui: &mut egui::Ui, sx: &backend::Sender) {
+        let Some(connect::Pending { from, to, mut fan }) = self.connecting else {
+            return;
+        };
+
+        let Some(from_node) = self.node(from) else {
+            self.connecting = None;
+            return;
+        };
+
+        ui.separator();
+
+        let Some(to_node) = to.and_then(|to| self.node(to)) else {
+            ui.label(format!(
+                "Connecting {}.\nChoose the node to connect to with ⮕ on its header.",
+                from_node.user_label
+            ));
+
+            if ui.button("Cancel").clicked() {
+                self.connecting = None;
+            }
+
+            return;
+        };
+
+        ui.label(format!("{} → {}", from_node.user_label, to_node.user_label));
+
+        ui.checkbox(&mut fan, "Mono fan-out/fan-in")
+            .on_hover_text("Link a single port to all the ports of the other node");
+
+        let outputs: Vec<connect::PortInfo> = from_node
+            .outputs
+            .iter()
+            .map(connect::PortInfo::of)
+            .collect();
+        let inputs: Vec<connect::PortInfo> =
+            to_node.inputs.iter().map(connect::PortInfo::of).collect();
+
+        let pairs = connect::pair(&outputs, &inputs, fan);
+
+        let existing: HashSet<(u32, u32)> = self
+            .links
+            .values()
+            .map(|link| (link.ends.1, link.ends.3))
+            .collect();
+
+        let port_name = |ports: &[Port], id: u32| {
+            ports
+                .iter()
+                .find(|port| port.id == id)
+                .map_or_else(|| id.to_string(), |port| port.name.clone())
+        };
+
+        egui::ScrollArea::vertical()
+            .id_salt("connect_preview")
+            .max_height(200.)
+            .show(ui, |ui| {
+                egui::Grid::new("connect_preview")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for &(output, input) in &pairs {
+                            ui.label(port_name(&from_node.outputs, output));
+                            ui.label("→");
+                            ui.label(port_name(&to_node.inputs, input));
+
+                            if existing.contains(&(output, input)) {
+                                ui.weak("Already linked");
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        let new: Vec<(u32, u32)> = pairs
+            .into_iter()
+            .filter(|pair| !existing.contains(pair))
+            .collect();
+
+        let mut done = false;
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !new.is_empty(),
+                    egui::Button::new(format!("Connect ({} links)", new.len())),
+                )
+                .on_disabled_hover_text("There are no ports left to link")
+                .clicked()
+            {
+                for &(output, input) in &new {
+                    create_link(sx, output, input);
+                }
+                done = true;
+            }
+
+            if ui.button("Cancel").clicked() {
+                done = true;
+            }
+        });
+
+        if done {
+            self.connecting = None;
+        } else if let Some(pending) = &mut self.connecting {
+            pending.fan = fan;
+        }
+    }
+
+    fn focus(&mut self, node_id: NodeId) {
+        const SCALE: f32 = 1.;
+
+        let Some(info) = self.snarl.get_node_info(node_id) else {
+            return;
+        };
+
+        // Around the middle of the header
+        let target = info.pos + egui::vec2(150., 30.);
+
+        self.restored_transform = Some(TSTransform::new(target.to_vec2() * SCALE, SCALE));
+    }
+
+    fn show_search(&mut self, ui: &mut egui::Ui) {
+        let submitted = ui
+            .horizontal(|ui| {
+                ui.label("Search");
+                let response = egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("ID, name, description, application or port")
+                    .show(ui)
+                    .response;
+
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))
+            })
+            .inner;
+
+        if self.search.is_empty() {
+            self.highlighted = None;
+            return;
+        }
+
+        let query = self.search.to_lowercase();
+
+        let shown = self.nodes.iter().filter_map(|(&id, &node_id)| {
+            let found = search::find(self.snarl.get_node(node_id)?, &query)?;
+            Some((id, node_id, found))
+        });
+
+        // Members of collapsed groups lead to their summaries
+        let collapsed = self.hidden.iter().filter_map(|(&id, hidden)| {
+            let found = search::find(&hidden.node, &query)?;
+            Some((id, self.represented(id)?, found))
+        });
+
+        let mut results: Vec<(u32, NodeId, String)> = shown.chain(collapsed).collect();
+        results.sort_unstable_by_key(|(id, ..)| *id);
+
+        let hidden_matches = self
+            .hidden
+            .iter()
+            .filter(|&(&id, hidden)| {
+                self.represented(id).is_none() && search::find(&hidden.node, &query).is_some()
+            })
+            .count();
+
+        // Enter goes to the first result
+        let mut focus = results
+            .first()
+            .filter(|_| submitted)
+            .map(|(id, node_id, _)| (*id, *node_id));
+
+        egui::ScrollArea::vertical()
+            .id_salt("search_results")
+            .max_height(200.)
+            .show(ui, |ui| {
+                for (id, node_id, found) in results.iter().take(search::MAX_RESULTS) {
+                    let label = self
+                        .nodes
+                        .get(id)
+                        .and_then(|&node_id| self.snarl.get_node(node_id))
+                        .or_else(|| self.hidden.get(id).map(|hidden| &hidden.node))
+                        .map_or_else(
+                            || id.to_string(),
+                            |node| format!("{id} {}", node.user_label),
+                        );
+
+                    if ui
+                        .selectable_label(self.highlighted == Some(*id), label)
+                        .on_hover_text(found)
+                        .clicked()
+                    {
+                        focus = Some((*id, *node_id));
+                    }
+                }
+            });
+
+        if results.is_empty() {
+            ui.weak("No matches");
+        } else if results.len() > search::MAX_RESULTS {
+            ui.weak(format!("{} more", results.len() - search::MAX_RESULTS));
+        }
+
+        if hidden_matches > 0 {
+            ui.weak(format!("{hidden_matches} hidden by the filters"));
+        }
+
+        if let Some((id, node_id)) = focus {
+            self.highlighted = Some(id);
+            self.focus(node_id);
+        }
+    }
+
+    fn export(&mut self, format: export::Format, selected: &[NodeId]) {
+        let scope = self.exporter.scope;
+        let selected: HashSet<NodeId> = selected.iter().copied().collect();
+
+        let in_scope = |id: u32| match scope {
+            export::Scope::All => true,
+            export::Scope::Shown => self.represented(id).is_some(),
+            export::Scope::Selected => self
+                .represented(id)
+                .is_some_and(|node_id| selected.contains(&node_id)),
+        };
+
+        let shown = self.nodes.iter().filter_map(|(&id, &node_id)| {
+            let info = self.snarl.get_node_info(node_id)?;
+            Some((
+                id,
+                export::Item {
+                    node: &info.value,
+                    pos: Some(info.pos),
+                },
+            ))
+        });
+
+        let hidden = self.hidden.iter().map(|(&id, hidden)| {
+            (
+                id,
+                export::Item {
+                    node: &hidden.node,
+                    pos: hidden.pos,
+                },
+            )
+        });
+
+        let mut items: Vec<(u32, export::Item)> = shown
+            .chain(hidden)
+            .filter(|(id, _)| in_scope(*id))
+            .collect();
+        items.sort_unstable_by_key(|(id, _)| *id);
+
+        let ids: HashSet<u32> = items.iter().map(|(id, _)| *id).collect();
+
+        let mut links: Vec<(u32, &Link)> = self
+            .links
+            .iter()
+            .filter(|(_, link)| ids.contains(&link.ends.0) && ids.contains(&link.ends.2))
+            .map(|(&id, link)| (id, link))
+            .collect();
+        links.sort_unstable_by_key(|(id, _)| *id);
+
+        let items: Vec<export::Item> = items.into_iter().map(|(_, item)| item).collect();
+        let links: Vec<&Link> = links.into_iter().map(|(_, link)| link).collect();
+
+        self.exporter.export(format, &items, &links);
+    }
+
+    /// Places the nodes in layers that follow the direction of the links between them,
+    /// ordered to minimize crossings, starting from the top left of their bounding box
+    #[allow(clippy::cast_precision_loss)]
+    fn arrange(&mut self, nodes: &[NodeId]) {
+        const LAYER_SPACING: f32 = 500.;
+        const NODE_SPACING: f32 = 40.;
+        // Space left for links passing through a layer
+        const DUMMY_HEIGHT: f32 = 30.;
+
+        let Some(origin) = nodes
+            .iter()
+            .filter_map(|&node| self.snarl.get_node_info(node))
+            .map(|info| info.pos)
+            .reduce(egui::Pos2::min)
+        else {
+            return;
+        };
+
+        let indices: HashMap<NodeId, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| (node, i))
+            .collect();
+
+        let edges: Vec<(usize, usize)> = self
+            .wires
+            .keys()
+            .filter_map(|(out, inp)| Some((*indices.get(&out.node)?, *indices.get(&inp.node)?)))
+            .collect();
+
+        let mut connected = vec![false; nodes.len()];
+        for &(from, to) in &edges {
+            connected[from] = true;
+            connected[to] = true;
+        }
+
+        // Unconnected nodes go in a column of their own, before the rest
+        let unconnected: Vec<Option<usize>> = (0..nodes.len())
+            .filter(|&i| !connected[i])
+            .map(Some)
+            .collect();
+
+        let layers = std::iter::once(unconnected)
+            .chain(
+                layout::layers(nodes.len(), &edges)
+                    .into_iter()
+                    .map(|layer| {
+                        layer
+                            .into_iter()
+                            .filter(|node| node.is_none_or(|i| connected[i]))
+                            .collect()
+                    }),
+            )
+            .filter(|layer: &Vec<Option<usize>>| !layer.is_empty());
+
+        // Nodes of each layer with their heights
+        let layers: Vec<Vec<(Option<usize>, f32)>> = layers
+            .map(|layer| {
+                layer
+                    .into_iter()
+                    .map(|node| {
+                        let height = node
+                            .and_then(|i| self.snarl.get_node_info(nodes[i]))
+                            .map_or(DUMMY_HEIGHT, |info| info.value.estimated_height(info.open));
+                        (node, height)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let total_height =
+            |layer: &[(Option<usize>, f32)]| layer.iter().map(|(_, h)| h + NODE_SPACING).sum();
+
+        let tallest = layers
+            .iter()
+            .map(|layer| total_height(layer))
+            .fold(0., f32::max);
+
+        for (column, layer) in layers.iter().enumerate() {
+            // Center the layers on each other
+            let mut y = origin.y + (tallest - total_height(layer)) / 2.;
+
+            for &(node, node_height) in layer {
+                if let Some(info) = node.and_then(|i| self.snarl.get_node_info_mut(nodes[i])) {
+                    info.pos = egui::pos2(origin.x + column as f32 * LAYER_SPACING, y);
+                    // Remember it as if the user placed it
+                    info.value.initial_auto_pos = None;
+                }
+
+                y += node_height + NODE_SPACING;
+            }
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, sx: &crate::backend::Sender) {
+        let style = SnarlStyle {
+            min_scale: Some(0.5),
+            max_scale: Some(1.5),
+            bg_pattern: Some(egui_snarl::ui::BackgroundPattern::grid(
+                egui::vec2(50., 50.),
+                0.,
+            )),
+            header_frame: Some(
+                egui::Frame::window(ui.style())
+                    .multiply_with_opacity(0.5)
+                    .shadow(egui::Shadow::NONE),
+            ),
+            node_frame: Some(
+                egui::Frame::window(ui.style())
+                    .multiply_with_opacity(0.85)
+                    .shadow(egui::Shadow::NONE),
+            ),
+            pin_placement: Some(egui_snarl::ui::PinPlacement::Edge),
+            wire_layer: Some(WireLayer::AboveNodes),
+            ..SnarlStyle::default()
+        };
+
+        self.apply_filter();
+
+        if !self.unpositioned.is_empty() {
+            const NODE_SPACING: egui::Vec2 = egui::vec2(300f32, 200f32);
+
+            let screen_pos_to_graph = |pos| {
+                (pos + self.transform.translation - ui.max_rect().center().to_vec2())
+                    / self.transform.scaling
+            };
+
+            let mut next_sources_pos = screen_pos_to_graph(egui::pos2(
+                ui.max_rect().left() + ui.style().spacing.window_margin.leftf(),
+                ui.max_rect().top() + 150.,
+            ));
+
+            let mut next_others_pos = screen_pos_to_graph(egui::pos2(
+                ui.max_rect().width() / 2.,
+                ui.max_rect().top() + 150.,
+            ));
+
+            let mut next_sinks_pos = screen_pos_to_graph(egui::pos2(
+                ui.max_rect().width() - 200.,
+                ui.max_rect().top() + 150.,
+            ));
+
+            for (node_id, pos, _) in self.snarl.nodes_pos_ids() {
+                if self.unpositioned.contains(&node_id) {
+                    continue;
+                }
+
+                // Determine next available position for this node's kind
+                for next in [
+                    &mut next_sources_pos,
+                    &mut next_others_pos,
+                    &mut next_sinks_pos,
+                ] {
+                    if (pos.x - 50f32..=pos.x + 50f32).contains(&next.x)
+                        && (next.y..next.y + NODE_SPACING.y).contains(&pos.y)
+                    {
+                        next.y += NODE_SPACING.y;
+                        break;
+                    }
+                }
+            }
+
+            // Position unpositioned nodes
+            for node in self.unpositioned.drain() {
+                let Some(node) = self.snarl.get_node_info_mut(node) else {
+                    eprintln!("Tried to position node not in snarl");
+                    continue;
+                };
+
+                let global = node.value.global.borrow();
+
+                let ports = global.info().and_then(|info| {
+                    info[2]
+                        .1
+                        .parse::<u32>()
+                        .ok()
+                        .zip(info[3].1.parse::<u32>().ok())
+                });
+
+                let new_pos = if let Some((inputs, outputs)) = ports {
+                    if inputs == 0 && outputs == 0 {
+                        &mut next_others_pos
+                    } else if inputs == 0 {
+                        &mut next_sources_pos
+                    } else if outputs == 0 {
+                        &mut next_sinks_pos
+                    } else {
+                        &mut next_others_pos
+                    }
+                } else {
+                    &mut next_others_pos
+                };
+
+                node.pos = *new_pos;
+                node.value.initial_auto_pos = Some(*new_pos);
+
+                new_pos.y += NODE_SPACING.y;
+            }
+        }
+
+        let hidden_links = self.hidden_links();
+        let appearances = self.link_appearances();
+        let port_links = self.port_links(&appearances);
+        let port_levels = self.port_levels();
+
+        let mut viewer = Viewer {
+            sx,
+            wires: &mut self.wires,
+            hidden_links: &hidden_links,
+            port_links: &port_links,
+            pin_positions: Rc::default(),
+            port_levels: &port_levels,
+            meters: &self.meters,
+            toggle_meter: None,
+            connecting_from: self.connecting.map(|pending| pending.from),
+            connect: None,
+            hide: None,
+            highlighted: self.highlighted,
+            expand: None,
+            rects: HashMap::new(),
+            layer: None,
+            transform: self.restored_transform.take(),
+        };
+
+        // Reserved before the nodes are drawn so that the groups are painted behind them
+        let groups_slot = self.snarl_layer.map(|layer| {
+            let painter = ui.ctx().layer_painter(layer).with_clip_rect(ui.max_rect());
+            let slot = painter.add(egui::Shape::Noop);
+            (painter, slot)
+        });
+
+        self.snarl.show(&mut viewer, &style, "graph", ui);
+
+        let rects = std::mem::take(&mut viewer.rects);
+        let layer = viewer.layer;
+        let pin_positions = std::mem::take(&mut *viewer.pin_positions.borrow_mut());
+
+        let selected = egui_snarl::ui::get_selected_nodes(ui.make_persistent_id("graph"), ui.ctx());
+
+        self.transform = viewer.transform.unwrap_or(self.transform);
+
+        if let Some(name) = viewer.hide {
+            self.filter.hide_name(name);
+        }
+
+        if let Some(parent) = viewer.expand {
+            self.collapsed.remove(&parent);
+        }
+
+        if let Some(id) = viewer.toggle_meter {
+            self.toggle_meter(id, sx);
+        }
+
+        if let Some(id) = viewer.connect {
+            self.choose_for_connecting(id);
+        }
+
+        self.snarl_layer = layer;
+
+        if let Some(layer) = layer {
+            if let Some(slot) = groups_slot {
+                self.show_groups(ui, &rects, layer, slot);
+            }
+            self.draw_wires(ui, &pin_positions, &appearances, layer);
+        }
+
+        let controls_layer_id =
+            egui::LayerId::new(ui.layer_id().order, ui.layer_id().id.with("controls"));
+        ui.scope_builder(
+            egui::UiBuilder::new()
+                .layer_id(controls_layer_id)
+                .max_rect(ui.max_rect().shrink(3.)),
+            |ui| {
+                egui::Frame::canvas(ui.style())
+                    .shadow(egui::Shadow::NONE)
+                    .inner_margin(egui::Margin::symmetric(5, 2))
+                    .stroke(ui.style().visuals.window_stroke)
+                    .show(ui, |ui| {
+                        egui::CollapsingHeader::new("Controls")
+                            .default_open(true)
+                            .show_unindented(ui, |ui| {
+                                ui.label(
+                                    "Reset view: Double click\n\
+                                    Pan: Click & drag\n\
+                                    Select nodes: Secondary click & drag\n\
+                                    Deselect nodes: Shift + secondary click & drag\n\
+                                    Zoom: Ctrl & scroll\n\
+                                    Start linking: Click & drag from port\n\
+                                    Destroy link: Click on link",
+                                );
+                            });
+
+                        self.show_connect(ui, sx);
+
+                        self.show_search(ui);
+
+                        egui::CollapsingHeader::new(format!(
+                            "Filters ({} hidden)",
+                            self.hidden.keys().filter(|&&id| self.represented(id).is_none()).count()
+                        ))
+                            .show_unindented(ui, |ui| {
+                                egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                                    self.filter.show(ui);
+                                });
+                            });
+
+                        egui::CollapsingHeader::new("Links").show_unindented(ui, wires::show_legend);
+
+                        egui::CollapsingHeader::new("Export").show_unindented(ui, |ui| {
+                            if let Some(format) = self.exporter.show(ui) {
+                                self.export(format, &selected);
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Auto-arrange all")
+                                .on_hover_text("Place all nodes in columns following the links between them")
+                                .clicked()
+                            {
+                                // Ordered by ID so that arranging again gives the same result
+                                let mut nodes: Vec<(u32, NodeId)> = self
+                                    .nodes
+                                    .iter()
+                                    .chain(&self.summaries)
+                                    .map(|(&id, &node)| (id, node))
+                                    .collect();
+                                nodes.sort_unstable_by_key(|(id, _)| *id);
+
+                                let nodes: Vec<NodeId> = nodes.into_iter().map(|(_, node)| node).collect();
+                                self.arrange(&nodes);
+                            }
+
+                            if ui
+                                .add_enabled(selected.len() > 1, egui::Button::new("Arrange selection"))
+                                .on_hover_text("Place the selected nodes in columns following the links between them")
+                                .clicked()
+                            {
+                                self.arrange(&selected);
+                            }
+                        });
+                    });
+            },
+        );
+
+        ui.ctx().set_sublayer(ui.layer_id(), controls_layer_id);
+    }
+
+    pub fn add_node(&mut self, global: &Rc<RefCell<Global>>) {
+        let global_ref = global.borrow();
+
+        if self.nodes.contains_key(&global_ref.id()) || self.hidden.contains_key(&global_ref.id()) {
+            return;
+        }
+
+        let props = global_ref.props();
+        let name = props.get("node.name");
+
+        // The streams of the meters would clutter the graph
+        if name.is_some_and(|name| name.starts_with(backend::METER_NAME_PREFIX)) {
+            return;
+        }
+
+        let pos = name
+            .and_then(|name| self.find_user_position(name, props.get("target.object")))
+            .unwrap_or(egui::Pos2::ZERO);
+
+        let node_id = self.snarl.insert_node(pos, Node::new(Rc::clone(global)));
+        self.nodes.insert(global_ref.id(), node_id);
+
+        if pos == egui::Pos2::ZERO {
+            self.unpositioned.insert(node_id);
+        }
+    }
+
+    pub fn add_input_port(&mut self, global: &Rc<RefCell<Global>>) {
+        let port_id = global.borrow().id();
+
+        if !self.ports.insert(port_id) {
+            return;
+        }
+
+        let Some(node) = global.borrow().parent_id().and_then(|id| self.node_mut(id)) else {
+            return;
+        };
+
+        node.inputs.push(Port {
+            id: port_id,
+            name: format!(
+                "{} ({})",
+                global.borrow().name().cloned().unwrap_or_default(),
+                global.borrow().id()
+            ),
+            global: Rc::clone(global),
+        });
+
+        node.resize = true;
+    }
+
+    pub fn add_output_port(&mut self, global: &Rc<RefCell<Global>>) {
+        let port_id = global.borrow().id();
+
+        if !self.ports.insert(port_id) {
+            return;
+        }
+
+        let Some(node) = global.borrow().parent_id().and_then(|id| self.node_mut(id)) else {
+            return;
+        };
+
+        node.outputs.push(Port {
+            id: port_id,
+            name: format!(
+                "{} ({})",
+                global.borrow().name().cloned().unwrap_or_default(),
+                global.borrow().id()
+            ),
+            global: Rc::clone(global),
+        });
+
+        node.resize = true;
+    }
+
+    pub fn add_link(
+        &mut self,
+        global: &Rc<RefCell<Global>>,
+        output_node: u32,
+        output_port: u32,
+        input_node: u32,
+        input_port: u32,
+    ) {
+        let link_id = global.borrow().id();
+        let ends = (output_node, output_port, input_node, input_port);
+
+        self.links.insert(
+            link_id,
+            Link {
+                ends,
+                global: Rc::clone(global),
+            },
+        );
+        self.connect_link(link_id, ends);
+    }
+
+    /// Adds the wire of a link if both of its nodes are shown or in collapsed groups
+    fn connect_link(
+        &mut self,
+        link_id: u32,
+        (output_node, output_port, input_node, input_port): LinkEnds,
+    ) {
+        let (Some(out_node_id), Some(in_node_id)) =
+            (self.represented(output_node), self.represented(input_node))
+        else {
+            return;
+        };
+
+        // Links within a collapsed group
+        if out_node_id == in_node_id {
+            return;
+        }
+
+        let (Some(out_node), Some(in_node)) = (
+            self.snarl.get_node(out_node_id),
+            self.snarl.get_node(in_node_id),
+        ) else {
+            return;
+        };
+
+        let (Some(output_port), Some(input_port)) = (
+            out_node
+                .outputs
+                .iter()
+                .enumerate()
+                .find_map(|(idx, p)| (p.id == output_port).then_some(idx)),
+            in_node
+                .inputs
+                .iter()
+                .enumerate()
+                .find_map(|(idx, p)| (p.id == input_port).then_some(idx)),
+        ) else {
+            return;
+        };
+
+        let out_pin_id = OutPinId {
+            node: out_node_id,
+            output: output_port,
+        };
+
+        let in_pin_id = InPinId {
+            node: in_node_id,
+            input: input_port,
+        };
+
+        if self.snarl.connect(out_pin_id, in_pin_id) {
+            self.wires.insert((out_pin_id, in_pin_id), link_id);
+        }
+    }
+
+    pub fn remove_link(&mut self, id: u32) {
+        self.links.remove(&id);
+
+        self.wires.retain(|(out, inp), &mut link_id| {
+            if link_id == id {
+                self.snarl.disconnect(*out, *inp);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    pub fn remove_port(&mut self, node_id: u32, port_id: u32) {
+        let Some(node) = self.node_mut(node_id) else {
+            return;
+        };
+
+        if self.ports.remove(&port_id) {
+            node.inputs.retain(|p| p.id != port_id);
+            node.outputs.retain(|p| p.id != port_id);
+        }
+    }
+
+    pub fn remove_node(&mut self, id: u32) {
+        // The backend stops the meter along with the node
+        self.meters.remove(&id);
+
+        self.hide_node(id);
+
+        if let Some(HiddenNode {
+            node,
+            pos: Some(pos),
+            ..
+        }) = self.hidden.remove(&id)
+        {
+            self.remember_position(&node, pos);
+        }
+    }
+}
+
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Default)]
+pub struct PersistentData {
+    positions: HashMap<String, VecDeque<UserNodePosInfo>>,
+    transform: TSTransform,
+}
+
+impl PersistentView for Graph {
+    type Data = PersistentData;
+
+    fn with_data(data: &Self::Data) -> Self {
+        Self {
+            user_positions: data.positions.clone(),
+            restored_transform: Some(data.transform),
+            ..Self::new()
+        }
+    }
+
+    fn save_data(&self) -> Option<Self::Data> {
+        let mut positions: HashMap<String, VecDeque<UserNodePosInfo>> = HashMap::new();
+
+        let hidden = self
+            .hidden
+            .values()
+            .filter_map(|hidden| Some((&hidden.node, hidden.pos?)));
+
+        for (node, pos) in self
+            .snarl
+            .nodes_info()
+            .filter(|info| info.value.summary.is_none())
+            .map(|info| (&info.value, info.pos))
+            .chain(hidden)
+        {
+            let global = node.global.borrow();
+            let target_object = global
+                .props()
+                .get("target.object")
+                .map(|t| self.to_target_name(t));
+
+            if let Some(name) = global.props().get("node.name") {
+                positions
+                    .entry(name.clone())
+                    .or_default()
+                    .push_back(UserNodePosInfo {
+                        target_object: target_object.map(Cow::into_owned),
+                        pos,
+                    });
+            }
+        }
+
+        for (name, infos) in &self.user_positions {
+            positions
+                .entry(name.clone())
+                .or_default()
+                .extend(infos.iter().cloned());
+        }
+
+        if positions.is_empty() {
+            None
+        } else {
+            Some(PersistentData {
+                positions,
+                transform: self.transform,
+            })
+        }
+    }
+}
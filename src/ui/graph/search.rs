@@ -0,0 +1,43 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::Node;
+
+/// Maximum number of results shown
+pub const MAX_RESULTS: usize = 20;
+
+/// Describes what of the node matches the lowercase query, if anything does
+pub fn find(node: &Node, query: &str) -> Option<String> {
+    let global = node.global.borrow();
+
+    if global.id().to_string() == query {
+        return Some(format!("ID {query}"));
+    }
+
+    for key in ["node.name", "node.description", "application.name"] {
+        if let Some(value) = global.props().get(key)
+            && value.to_lowercase().contains(query)
+        {
+            return Some(format!("{key}: {value}"));
+        }
+    }
+
+    node.inputs
+        .iter()
+        .chain(&node.outputs)
+        .find(|port| port.name.to_lowercase().contains(query))
+        .map(|port| format!("Port {}", port.name))
+}
@@ -0,0 +1,346 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! How the wires of links are drawn.
+//! Snarl takes the wire appearance from the pins, which are shared by all of the links
+//! of a port, so it's told not to draw the wires of linked pins and they're drawn here,
+//! one for each link.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use eframe::egui;
+use egui_snarl::{
+    InPinId, OutPinId,
+    ui::{PinInfo, PinWireInfo, SnarlPin, SnarlStyle},
+};
+
+use crate::ui::globals_store::Global;
+
+/// Width of the wires at a scale of 1
+const WIDTH: f32 = 2.;
+
+/// A curve that leaves and enters the pins horizontally, like snarl's wires
+fn curve(from: egui::Pos2, to: egui::Pos2, scale: f32) -> egui::epaint::CubicBezierShape {
+    let bend = egui::vec2(((to.x - from.x).abs() / 2.).max(40. * scale), 0.);
+
+    egui::epaint::CubicBezierShape::from_points_stroke(
+        [from, from + bend, to - bend, to],
+        false,
+        egui::Color32::TRANSPARENT,
+        egui::Stroke::NONE,
+    )
+}
+
+#[derive(Clone)]
+pub enum State {
+    Active,
+    Paused,
+    /// Init, allocating or negotiating
+    Starting(String),
+    Unlinked,
+    Error(String),
+}
+
+impl State {
+    /// As reported by the link listener, which reports the message of errors in place of the state
+    fn parse(state: &str) -> Self {
+        match state {
+            "Active" => Self::Active,
+            "Paused" => Self::Paused,
+            "Init" | "Allocating" | "Negotiating" => Self::Starting(state.to_owned()),
+            "Unlinked" => Self::Unlinked,
+            error => Self::Error(error.to_owned()),
+        }
+    }
+
+    fn color(&self) -> egui::Color32 {
+        match self {
+            Self::Active => egui::Color32::from_rgb(80, 200, 120),
+            Self::Paused => egui::Color32::from_rgb(120, 140, 160),
+            Self::Starting(_) => egui::Color32::from_rgb(230, 180, 60),
+            Self::Unlinked => egui::Color32::DARK_GRAY,
+            Self::Error(_) => egui::Color32::from_rgb(230, 60, 60),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Active => "Active",
+            Self::Paused => "Paused",
+            Self::Starting(state) => state,
+            Self::Unlinked => "Unlinked",
+            Self::Error(_) => "Error",
+        }
+    }
+}
+
+/// State and kind of a link
+#[derive(Clone)]
+pub struct Appearance {
+    pub id: u32,
+    pub state: State,
+    /// Doesn't keep its nodes running
+    pub passive: bool,
+    /// Goes back to an earlier node of the graph
+    pub feedback: bool,
+}
+
+impl Appearance {
+    pub fn of(link: &Global) -> Self {
+        let state = link
+            .info()
+            .and_then(|info| info.iter().find(|(k, _)| *k == "State"))
+            .map_or(State::Starting("Init".to_owned()), |(_, state)| {
+                State::parse(state)
+            });
+
+        let flag = |key| link.props().get(key).is_some_and(|v| v == "true");
+
+        Self {
+            id: link.id(),
+            state,
+            passive: flag("link.passive"),
+            feedback: flag("link.feedback"),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let mut description = format!("Link {}: {}", self.id, self.state.as_str());
+
+        if let State::Error(message) = &self.state {
+            description.push_str(&format!(" ({message})"));
+        }
+        if self.passive {
+            description.push_str(", passive");
+        }
+        if self.feedback {
+            description.push_str(", feedback");
+        }
+
+        description
+    }
+}
+
+impl Appearance {
+    /// Draws the wire of the link between the pins
+    pub fn draw(&self, painter: &egui::Painter, from: egui::Pos2, to: egui::Pos2, scale: f32) {
+        let mut color = self.state.color();
+        if self.passive {
+            color = color.gamma_multiply(0.5);
+        }
+
+        let stroke = egui::Stroke::new(WIDTH * scale, color);
+        let curve = curve(from, to, scale);
+        let points = curve.flatten(Some(0.25));
+
+        if matches!(self.state, State::Active) {
+            painter.line(points, stroke);
+        } else {
+            painter.extend(egui::Shape::dashed_line(
+                &points,
+                stroke,
+                8. * scale,
+                6. * scale,
+            ));
+        }
+
+        if self.feedback {
+            painter.text(
+                curve.sample(0.5),
+                egui::Align2::CENTER_CENTER,
+                "↺",
+                egui::FontId::proportional(16. * scale),
+                color,
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PinId {
+    In(InPinId),
+    Out(OutPinId),
+}
+
+/// Where snarl drew each pin in the last frame
+#[derive(Default)]
+pub struct PinPositions {
+    pub inputs: HashMap<InPinId, egui::Pos2>,
+    pub outputs: HashMap<OutPinId, egui::Pos2>,
+    /// Pins whose wires snarl doesn't draw, with the color of their wires
+    hidden: Vec<(PinId, egui::Rect, egui::Color32)>,
+}
+
+impl PinPositions {
+    /// Draws the wire being dragged out of a pin whose wires snarl doesn't draw
+    pub fn draw_dragged(&self, ui: &egui::Ui, painter: &egui::Painter, scale: f32) {
+        let (origin, pointer) = ui.input(|i| {
+            (
+                i.pointer
+                    .is_decidedly_dragging()
+                    .then(|| i.pointer.press_origin())
+                    .flatten(),
+                i.pointer.interact_pos(),
+            )
+        });
+
+        let (Some(origin), Some(pointer)) = (origin, pointer) else {
+            return;
+        };
+
+        let Some(&(id, rect, color)) = self
+            .hidden
+            .iter()
+            .find(|(_, rect, _)| rect.contains(origin))
+        else {
+            return;
+        };
+
+        let (from, to) = match id {
+            PinId::Out(_) => (rect.center(), pointer),
+            PinId::In(_) => (pointer, rect.center()),
+        };
+
+        painter.line(
+            curve(from, to, scale).flatten(Some(0.25)),
+            egui::Stroke::new(WIDTH * scale, color),
+        );
+    }
+}
+
+/// A pin that records where it's drawn, so that the wires can be drawn over
+pub struct Pin {
+    id: PinId,
+    info: PinInfo,
+    /// Color of the wires if snarl is told not to draw them
+    hidden_wires: Option<egui::Color32>,
+    positions: Rc<RefCell<PinPositions>>,
+}
+
+impl Pin {
+    fn new(
+        id: PinId,
+        info: PinInfo,
+        hidden_wires: Option<egui::Color32>,
+        positions: &Rc<RefCell<PinPositions>>,
+    ) -> Self {
+        Self {
+            id,
+            info: if hidden_wires.is_some() {
+                info.with_wire_color(egui::Color32::TRANSPARENT)
+            } else {
+                info
+            },
+            hidden_wires,
+            positions: Rc::clone(positions),
+        }
+    }
+
+    pub fn input(
+        id: InPinId,
+        info: PinInfo,
+        hidden_wires: Option<egui::Color32>,
+        positions: &Rc<RefCell<PinPositions>>,
+    ) -> Self {
+        Self::new(PinId::In(id), info, hidden_wires, positions)
+    }
+
+    pub fn output(
+        id: OutPinId,
+        info: PinInfo,
+        hidden_wires: Option<egui::Color32>,
+        positions: &Rc<RefCell<PinPositions>>,
+    ) -> Self {
+        Self::new(PinId::Out(id), info, hidden_wires, positions)
+    }
+}
+
+impl SnarlPin for Pin {
+    fn draw(
+        self,
+        snarl_style: &SnarlStyle,
+        style: &egui::Style,
+        rect: egui::Rect,
+        painter: &egui::Painter,
+    ) -> PinWireInfo {
+        let mut positions = self.positions.borrow_mut();
+        match self.id {
+            PinId::In(id) => positions.inputs.insert(id, rect.center()),
+            PinId::Out(id) => positions.outputs.insert(id, rect.center()),
+        };
+
+        if let Some(color) = self.hidden_wires {
+            positions.hidden.push((self.id, rect, color));
+        }
+
+        self.info.draw(snarl_style, style, rect, painter)
+    }
+}
+
+/// The links of a port
+pub struct PortLinks(Vec<Appearance>);
+
+impl PortLinks {
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, link: Appearance) {
+        self.0.push(link);
+    }
+
+    /// Shows the states of the links when hovering the port,
+    /// and marks the port if any of its links failed
+    pub fn show(&self, ui: &mut egui::Ui, port_name: Option<egui::Response>) {
+        if let Some(port_name) = port_name {
+            port_name.on_hover_ui(|ui| {
+                for link in &self.0 {
+                    ui.colored_label(link.state.color(), link.describe());
+                }
+            });
+        }
+
+        let errors: Vec<String> = self
+            .0
+            .iter()
+            .filter(|link| matches!(link.state, State::Error(_)))
+            .map(Appearance::describe)
+            .collect();
+
+        if !errors.is_empty() {
+            ui.colored_label(ui.visuals().error_fg_color, "⚠")
+                .on_hover_text(errors.join("\n"));
+        }
+    }
+}
+
+/// Explains the colors and styles of the wires
+pub fn show_legend(ui: &mut egui::Ui) {
+    for state in [
+        State::Active,
+        State::Paused,
+        State::Starting("Negotiating".to_owned()),
+        State::Unlinked,
+        State::Error(String::new()),
+    ] {
+        ui.colored_label(state.color(), state.as_str());
+    }
+
+    ui.label("Links that aren't active are dashed");
+    ui.label("Passive links are dimmer");
+    ui.label("Feedback links are marked with ↺");
+    ui.label("Ports with failed links are marked with ⚠");
+}
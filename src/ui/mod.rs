@@ -14,6 +14,8 @@
 //
 // SPDX-License-Identifier: GPL-3.0-only
 
+mod alerts;
+mod applications;
 mod context_manager;
 mod globals_store;
 mod graph;
@@ -22,6 +24,8 @@ mod object_creator;
 mod profiler;
 mod util;
 
+use alerts::Alerts;
+use applications::Applications;
 use context_manager::ContextManager;
 use globals_store::GlobalsStore;
 use graph::Graph;
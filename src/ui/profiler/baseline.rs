@@ -0,0 +1,450 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Saved profiler measurements to compare the live ones against
+
+use std::collections::BTreeMap;
+
+use eframe::egui;
+use egui_plot::PlotPoints;
+
+use super::{
+    data::{
+        Client, Driver, DriverMetric, NodeMetric, NodeStats, Normalization, Positions, Span, XAxis,
+    },
+    stats::Stats,
+};
+
+/// Points of a series for each kind of X axis.
+/// Saved downsampled, which is enough for an overlay.
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default)]
+pub struct Series {
+    cycle: Vec<[f64; 2]>,
+    time: Vec<[f64; 2]>,
+}
+
+/// Number of columns the series are downsampled to
+const COLUMNS: usize = 2048;
+
+/// Number of columns the series are downsampled to when persisted,
+/// since all of the baselines are saved with every save of the app's state
+const PERSISTED_COLUMNS: usize = 256;
+
+/// Merges consecutive points so that each column has 2 points,
+/// the first X of the merged points with their minimum and maximum Y.
+/// Gaps, which are points with a NaN Y, are kept.
+fn downsample(points: &[[f64; 2]], columns: usize) -> Vec<[f64; 2]> {
+    let columns = columns.max(1);
+
+    // Downsampled points have at most 3 points per column, including gaps
+    if points.len() <= columns * 3 {
+        return points.to_vec();
+    }
+
+    let chunk = points.len().div_ceil(columns);
+
+    let mut downsampled = Vec::with_capacity(points.len() / chunk * 2 + 1);
+
+    for merged in points.chunks(chunk) {
+        let x = merged[0][0];
+
+        if merged.iter().any(|[_, y]| y.is_nan()) {
+            downsampled.push([x, f64::NAN]);
+        }
+
+        // f64::min and f64::max ignore NaNs
+        let (min, max) = merged
+            .iter()
+            .fold((f64::NAN, f64::NAN), |(min, max), [_, y]| {
+                (min.min(*y), max.max(*y))
+            });
+
+        if !min.is_nan() {
+            downsampled.push([x, min]);
+            downsampled.push([x, max]);
+        }
+    }
+
+    downsampled
+}
+
+impl Series {
+    fn new(driver: &Driver, points: impl Fn(&Positions, Span) -> PlotPoints<'static>) -> Self {
+        let points = |axis| {
+            let positions = driver.positions(axis);
+            points(&positions, positions.full_span(COLUMNS))
+                .points()
+                .iter()
+                .map(|p| [p.x, p.y])
+                .collect()
+        };
+
+        Self {
+            cycle: points(XAxis::Cycle),
+            time: points(XAxis::Time),
+        }
+    }
+
+    fn downsampled(&self, columns: usize) -> Self {
+        Self {
+            cycle: downsample(&self.cycle, columns),
+            time: downsample(&self.time, columns),
+        }
+    }
+
+    pub fn points(&self, axis: XAxis) -> PlotPoints<'static> {
+        match axis {
+            XAxis::Cycle => self.cycle.clone(),
+            XAxis::Time => self.time.clone(),
+        }
+        .into()
+    }
+}
+
+/// Mean and 99th percentile of a measurement
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy)]
+pub struct Summary {
+    mean: f64,
+    p99: f64,
+}
+
+impl From<&Stats> for Summary {
+    fn from(stats: &Stats) -> Self {
+        Self {
+            mean: stats.mean,
+            p99: stats.p99,
+        }
+    }
+}
+
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default)]
+pub struct NodeBaseline {
+    /// Series of the node measurements, in the order of [`NODE_METRICS`]
+    series: [Series; 3],
+    summaries: [Option<Summary>; 3],
+}
+
+const NODE_METRICS: [NodeMetric; 3] = [
+    NodeMetric::EndDate,
+    NodeMetric::SchedulingLatency,
+    NodeMetric::Duration,
+];
+
+const fn node_metric_index(metric: NodeMetric) -> usize {
+    match metric {
+        NodeMetric::EndDate => 0,
+        NodeMetric::SchedulingLatency => 1,
+        NodeMetric::Duration => 2,
+    }
+}
+
+fn summaries(stats: &NodeStats) -> [Option<Summary>; 3] {
+    [
+        stats.end_date.as_ref().map(Summary::from),
+        stats.scheduling_latency.as_ref().map(Summary::from),
+        stats.duration.as_ref().map(Summary::from),
+    ]
+}
+
+impl NodeBaseline {
+    pub fn series(&self, metric: NodeMetric) -> &Series {
+        &self.series[node_metric_index(metric)]
+    }
+}
+
+/// The retained measurements of a driver and its followers.
+/// Nodes are identified by their names since IDs change between runs.
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
+pub struct Baseline {
+    pub name: String,
+    driver: String,
+    /// Series of the driver measurements, in the order of [`DRIVER_METRICS`]
+    driver_series: [Series; 4],
+    nodes: BTreeMap<String, NodeBaseline>,
+}
+
+const DRIVER_METRICS: [DriverMetric; 4] = [
+    DriverMetric::Delay,
+    DriverMetric::Period,
+    DriverMetric::Estimated,
+    DriverMetric::EndDate,
+];
+
+impl Baseline {
+    pub fn new(name: String, driver: &Driver) -> Self {
+        let driver_name = driver.name().unwrap_or("Driver").to_owned();
+
+        let driver_series = DRIVER_METRICS.map(|metric| {
+            Series::new(driver, |p, s| {
+                driver.metric(metric, Normalization::None, p, s)
+            })
+        });
+
+        let mut nodes = BTreeMap::new();
+
+        nodes.insert(
+            driver_name.clone(),
+            NodeBaseline {
+                // The driver's node measurements aren't plotted
+                series: Default::default(),
+                summaries: summaries(&driver.stats().node),
+            },
+        );
+
+        for client in driver.clients() {
+            let series = NODE_METRICS.map(|metric| {
+                let measurement = match metric {
+                    NodeMetric::EndDate => Client::end_date,
+                    NodeMetric::SchedulingLatency => Client::scheduling_latency,
+                    NodeMetric::Duration => Client::duration,
+                };
+                Series::new(driver, |p, s| measurement(client, p, s))
+            });
+
+            nodes.insert(
+                client.name().to_owned(),
+                NodeBaseline {
+                    series,
+                    summaries: summaries(driver.client_stats(client)),
+                },
+            );
+        }
+
+        Self {
+            name,
+            driver: driver_name,
+            driver_series,
+            nodes,
+        }
+    }
+
+    /// The baseline with its series downsampled further, for persisting
+    pub fn for_storage(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            driver: self.driver.clone(),
+            driver_series: self
+                .driver_series
+                .each_ref()
+                .map(|series| series.downsampled(PERSISTED_COLUMNS)),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|(name, node)| {
+                    (
+                        name.clone(),
+                        NodeBaseline {
+                            series: node
+                                .series
+                                .each_ref()
+                                .map(|series| series.downsampled(PERSISTED_COLUMNS)),
+                            summaries: node.summaries,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn driver_series(&self, metric: DriverMetric) -> &Series {
+        let i = DRIVER_METRICS
+            .iter()
+            .position(|&m| m == metric)
+            .unwrap_or_default();
+        &self.driver_series[i]
+    }
+
+    pub fn node(&self, name: &str) -> Option<&NodeBaseline> {
+        self.nodes.get(name)
+    }
+
+    /// Shows the mean and p99 of the nodes of the baseline and of the driver, and their differences
+    pub fn show_summary(&self, ui: &mut egui::Ui, driver: &Driver) {
+        fn delta(ui: &mut egui::Ui, baseline: f64, current: f64) {
+            let diff = current - baseline;
+            let text = format!(
+                "{diff:+.1}us ({:+.1}%)",
+                diff / baseline.abs().max(f64::EPSILON) * 100.
+            );
+
+            // Higher is worse for all measurements
+            if diff > 0. {
+                ui.colored_label(ui.visuals().warn_fg_color, text);
+            } else {
+                ui.label(text);
+            }
+        }
+
+        let mut current = BTreeMap::new();
+        if let Some(name) = driver.name() {
+            current.insert(name, summaries(&driver.stats().node));
+        }
+        for client in driver.clients() {
+            current.insert(client.name(), summaries(driver.client_stats(client)));
+        }
+
+        ui.label(format!("Baseline of {}", self.driver));
+
+        egui::ScrollArea::both().max_height(300.).show(ui, |ui| {
+            egui::Grid::new("baseline_summary")
+                .striped(true)
+                .num_columns(8)
+                .show(ui, |ui| {
+                    for header in [
+                        "Node", "Metric", "Mean", "Now", "Δ Mean", "P99", "Now", "Δ P99",
+                    ] {
+                        ui.label(header);
+                    }
+                    ui.end_row();
+
+                    for (name, node) in &self.nodes {
+                        let now = current.get(name.as_str());
+
+                        for (i, metric) in NODE_METRICS.into_iter().enumerate() {
+                            let Some(baseline) = node.summaries[i] else {
+                                continue;
+                            };
+
+                            ui.label(name);
+                            ui.label(metric.as_str());
+
+                            let now = now.and_then(|summaries| summaries[i]);
+
+                            for (baseline, now) in [
+                                (baseline.mean, now.map(|s| s.mean)),
+                                (baseline.p99, now.map(|s| s.p99)),
+                            ] {
+                                ui.label(format!("{baseline:.1}us"));
+                                if let Some(now) = now {
+                                    ui.label(format!("{now:.1}us"));
+                                    delta(ui, baseline, now);
+                                } else {
+                                    ui.label("-");
+                                    ui.label("-");
+                                }
+                            }
+
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Weak;
+
+    use crate::backend::pods::profiler::Profiling;
+
+    use super::*;
+
+    fn profiling(cycle: i64) -> Profiling {
+        let mut p = Profiling::test(cycle, cycle * 1_000_000, Vec::new());
+        // Varies so that downsampling has extremes to keep
+        p.driver.finish = p.driver.signal + (cycle % 100) * 1000;
+        p
+    }
+
+    fn driver(cycles: i64) -> Driver {
+        let mut driver = Driver::with_max_profilings(100_000, Weak::new());
+        for cycle in 1..=cycles {
+            driver.add_profiling(profiling(cycle), 100_000, 0, &|_| None);
+        }
+        driver
+    }
+
+    fn y_range(points: &[[f64; 2]]) -> (f64, f64) {
+        points
+            .iter()
+            .fold((f64::NAN, f64::NAN), |(min, max), [_, y]| {
+                (min.min(*y), max.max(*y))
+            })
+    }
+
+    #[test]
+    fn downsampling() {
+        let mut points: Vec<[f64; 2]> = (0..10_000)
+            .map(|i| [f64::from(i), f64::from(i % 100)])
+            .collect();
+        points[5000][1] = f64::NAN;
+
+        let downsampled = downsample(&points, 100);
+
+        assert!(downsampled.len() <= 300);
+        assert_eq!(downsampled[0][0], 0.);
+        assert_eq!(y_range(&downsampled), (0., 99.));
+        assert!(downsampled.iter().any(|[_, y]| y.is_nan()));
+
+        // Downsampling again, as happens with every save, changes nothing
+        let again = downsample(&downsampled, 100);
+        assert_eq!(again.len(), downsampled.len());
+        assert!(
+            again
+                .iter()
+                .zip(&downsampled)
+                .all(|(a, b)| a[0] == b[0] && (a[1] == b[1] || a[1].is_nan() && b[1].is_nan()))
+        );
+    }
+
+    #[test]
+    fn overlay_alignment() {
+        let driver = driver(2000);
+        let baseline = Baseline::new("test".to_owned(), &driver);
+
+        // The baseline is drawn like the live plot of all of the samples
+        for axis in [XAxis::Cycle, XAxis::Time] {
+            let positions = driver.positions(axis);
+            let live = driver.metric(
+                DriverMetric::EndDate,
+                Normalization::None,
+                &positions,
+                positions.full_span(COLUMNS),
+            );
+            let saved = baseline.driver_series(DriverMetric::EndDate).points(axis);
+
+            assert_eq!(live.points().len(), saved.points().len());
+            assert!(
+                live.points()
+                    .iter()
+                    .zip(saved.points())
+                    .all(|(a, b)| a.x == b.x && a.y == b.y)
+            );
+        }
+
+        // Persisting keeps the start and the extremes so the overlay stays in place
+        let stored = baseline.for_storage();
+        for axis in [XAxis::Cycle, XAxis::Time] {
+            let full = &baseline.driver_series(DriverMetric::EndDate);
+            let stored = &stored.driver_series(DriverMetric::EndDate);
+
+            let (full, stored) = match axis {
+                XAxis::Cycle => (&full.cycle, &stored.cycle),
+                XAxis::Time => (&full.time, &stored.time),
+            };
+
+            assert!(stored.len() <= PERSISTED_COLUMNS * 3);
+            assert_eq!(stored[0][0], 0.);
+            assert_eq!(stored[0][0], full[0][0]);
+            assert_eq!(y_range(stored), y_range(full));
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+
+/// Equally sized buckets spanning a range of values.
+/// When logarithmic, the buckets are equally sized in log10 space
+/// and the positions returned are log10 of the values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Buckets {
+    start: f64,
+    width: f64,
+    count: usize,
+    log: bool,
+}
+
+impl Buckets {
+    /// Buckets spanning the values, ignoring NaNs and, if logarithmic, non-positive values.
+    /// Returns [`None`] if there are no such values.
+    pub fn spanning(values: impl Iterator<Item = f64>, count: usize, log: bool) -> Option<Self> {
+        let (min, max) = values
+            .filter(|v| !v.is_nan() && (!log || *v > 0.))
+            .map(|v| if log { v.log10() } else { v })
+            .fold(None, |acc: Option<(f64, f64)>, v| {
+                Some(acc.map_or((v, v), |(min, max)| (min.min(v), max.max(v))))
+            })?;
+
+        let count = count.max(1);
+
+        // Avoid zero width buckets when all values are equal
+        let width = if max > min {
+            (max - min) / count as f64
+        } else {
+            1.
+        };
+
+        Some(Self {
+            start: min,
+            width,
+            count,
+            log,
+        })
+    }
+
+    /// Width of a bucket in plot coordinates
+    pub const fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Position of the center of the bucket in plot coordinates
+    pub fn center(&self, bucket: usize) -> f64 {
+        (bucket as f64 + 0.5).mul_add(self.width, self.start)
+    }
+
+    /// Converts a position in plot coordinates back to a value
+    pub fn value_at(&self, position: f64) -> f64 {
+        if self.log {
+            10f64.powf(position)
+        } else {
+            position
+        }
+    }
+
+    /// The range of values the bucket holds
+    pub fn range(&self, bucket: usize) -> (f64, f64) {
+        let start = (bucket as f64).mul_add(self.width, self.start);
+        (self.value_at(start), self.value_at(start + self.width))
+    }
+
+    /// Number of values in each bucket, and the number of non-positive values
+    /// that were left out because they can't be placed on a logarithmic scale.
+    /// Values outside of the range are counted in the first or last bucket.
+    pub fn counts(&self, values: impl Iterator<Item = f64>) -> (Vec<usize>, usize) {
+        let mut counts = vec![0; self.count];
+        let mut non_positive = 0;
+
+        for value in values.filter(|v| !v.is_nan()) {
+            let position = match (self.log, value > 0.) {
+                (false, _) => value,
+                (true, true) => value.log10(),
+                (true, false) => {
+                    non_positive += 1;
+                    continue;
+                }
+            };
+
+            let bucket = ((position - self.start) / self.width).floor().max(0.) as usize;
+
+            counts[bucket.min(self.count - 1)] += 1;
+        }
+
+        (counts, non_positive)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linear() {
+        let buckets = Buckets::spanning([0., 10., f64::NAN].into_iter(), 5, false).unwrap();
+
+        assert_eq!(buckets.width(), 2.);
+        assert_eq!(buckets.center(0), 1.);
+        assert_eq!(buckets.range(4), (8., 10.));
+        assert_eq!(
+            buckets.counts([0., 1.9, 2., 10., -1., f64::NAN].into_iter()),
+            (vec![3, 1, 0, 0, 1], 0)
+        );
+    }
+
+    #[test]
+    fn log() {
+        let buckets = Buckets::spanning([-5., 0., 10., 1000.].into_iter(), 2, true).unwrap();
+
+        assert_eq!(buckets.width(), 1.);
+        assert_eq!(buckets.range(0), (10., 100.));
+        assert_eq!(
+            buckets.counts([0., 50., 2000., 100., -3.].into_iter()),
+            (vec![1, 2], 2)
+        );
+    }
+
+    #[test]
+    fn equal_values() {
+        let buckets = Buckets::spanning([3., 3.].into_iter(), 10, false).unwrap();
+
+        assert_eq!(buckets.counts([3., 3.].into_iter()).0[0], 2);
+        assert!(Buckets::spanning([0.].into_iter(), 10, true).is_none());
+    }
+}
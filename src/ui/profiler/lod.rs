@@ -0,0 +1,232 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+#![allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+
+use std::collections::VecDeque;
+
+/// Number of blocks of a level that make up a block of the next level
+const FACTOR: usize = 8;
+
+/// Enough for blocks of 8^7 = 2,097,152 samples
+const LEVELS: usize = 7;
+
+const fn block_size(level: usize) -> usize {
+    FACTOR.pow(level as u32 + 1)
+}
+
+fn merge(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    // f64::min and f64::max ignore NaNs
+    (a.0.min(b.0), a.1.max(b.1))
+}
+
+/// Minimums and maximums of consecutive blocks of samples
+#[derive(Default)]
+struct Level {
+    blocks: VecDeque<(f64, f64)>,
+    /// Index of the first block counting from the first sample ever pushed
+    first_block: usize,
+}
+
+/// A ring buffer of measurements that keeps a pyramid of the minimums and maximums
+/// of increasingly bigger blocks of them, so the minimum and maximum of any range
+/// can be found without going through all of its samples.
+///
+/// Blocks are aligned to the index of the samples counting from the first sample
+/// ever pushed, so dropping samples from the front doesn't require rebuilding them.
+#[derive(Default)]
+pub struct Column {
+    values: VecDeque<f64>,
+    /// Index of the first retained sample counting from the first sample ever pushed
+    first: usize,
+    levels: [Level; LEVELS],
+}
+
+impl Column {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&f64> {
+        self.values.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.values.iter()
+    }
+
+    fn pop_front(&mut self) {
+        if self.values.pop_front().is_none() {
+            return;
+        }
+
+        self.first += 1;
+
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            let size = block_size(i);
+
+            // Drop the blocks that are entirely before the first sample
+            while !level.blocks.is_empty() && (level.first_block + 1) * size <= self.first {
+                level.blocks.pop_front();
+                level.first_block += 1;
+            }
+        }
+    }
+
+    /// Push a value back while making the buffer hold at most `max` values
+    pub fn push_back(&mut self, max: usize, value: f64) {
+        if max == 0 {
+            self.clear();
+            return;
+        }
+
+        while self.len() >= max {
+            self.pop_front();
+        }
+
+        let index = self.first + self.values.len();
+        self.values.push_back(value);
+
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            let block = index / block_size(i);
+
+            if level.blocks.is_empty() {
+                level.first_block = block;
+            }
+
+            if block < level.first_block + level.blocks.len() {
+                let back = level.blocks.back_mut().unwrap();
+                *back = merge(*back, (value, value));
+            } else {
+                level.blocks.push_back((value, value));
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Drops the oldest values so that at most `max` are held
+    pub fn resize(&mut self, max: usize) {
+        while self.len() > max {
+            self.pop_front();
+        }
+    }
+
+    /// Minimum and maximum of the values in the range, ignoring NaNs.
+    /// Both are NaN if there are no such values.
+    pub fn min_max(&self, start: usize, end: usize) -> (f64, f64) {
+        let mut start = self.first + start;
+        let end = self.first + end.min(self.len());
+
+        let mut result = (f64::NAN, f64::NAN);
+
+        while start < end {
+            // Use the biggest block that starts here and fits in the range
+            let mut level = 0;
+            while level < LEVELS
+                && start % block_size(level) == 0
+                && start + block_size(level) <= end
+            {
+                level += 1;
+            }
+
+            if level == 0 {
+                let value = self.values[start - self.first];
+                result = merge(result, (value, value));
+                start += 1;
+            } else {
+                let size = block_size(level - 1);
+                let level = &self.levels[level - 1];
+
+                result = merge(result, level.blocks[start / size - level.first_block]);
+                start += size;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn brute_force(values: &[f64], start: usize, end: usize) -> (f64, f64) {
+        values[start..end]
+            .iter()
+            .fold((f64::NAN, f64::NAN), |acc, &v| merge(acc, (v, v)))
+    }
+
+    #[test]
+    fn min_max() {
+        let max = 1000;
+        let mut column = Column::with_capacity(max);
+        let mut values = Vec::new();
+
+        // Pseudo-random values, with the front getting dropped
+        for i in 0..2500u64 {
+            let value = ((i * 7919) % 1013) as f64;
+            column.push_back(max, value);
+            values.push(value);
+        }
+
+        let values = &values[values.len() - max..];
+        assert_eq!(column.len(), max);
+
+        for (start, end) in [
+            (0, 1000),
+            (1, 999),
+            (3, 517),
+            (512, 1000),
+            (0, 1),
+            (64, 128),
+        ] {
+            assert_eq!(
+                column.min_max(start, end),
+                brute_force(values, start, end),
+                "{start}..{end}"
+            );
+        }
+    }
+
+    #[test]
+    fn nan_and_resize() {
+        let mut column = Column::default();
+
+        for v in [f64::NAN, 3., f64::NAN, -1., 5.] {
+            column.push_back(10, v);
+        }
+
+        assert_eq!(column.min_max(0, 5), (-1., 5.));
+        assert!(column.min_max(0, 1).0.is_nan());
+
+        column.resize(2);
+        assert_eq!(column.len(), 2);
+        assert_eq!(column.min_max(0, 2), (-1., 5.));
+
+        column.clear();
+        assert_eq!(column.len(), 0);
+    }
+}
@@ -0,0 +1,643 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Reading and writing of the log files produced by `pw-profiler`.
+//!
+//! Each line of the log is one profiler sample of a single driver.
+//! The first 4 tab separated columns describe the driver (period, end date, delay
+//! and estimated period, all in microseconds) and they are followed by 8 columns
+//! for each follower slot (ID, signal, awake and finish times relative to the
+//! driver's signal, scheduling latency, duration, status and a reserved 0).
+//! Slots of followers that were not part of a cycle are filled with single spaces.
+//!
+//! The log itself does not contain follower names, `pw-profiler` only writes them
+//! in the gnuplot scripts it generates next to the log.
+
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use pipewire::spa::utils::Fraction;
+
+use crate::backend::pods::profiler::{Clock, Info, NodeBlock, Profiling};
+
+/// Same as `pw-profiler`
+const MAX_FOLLOWERS: usize = 64;
+
+const DRIVER_COLUMNS: usize = 4;
+const FOLLOWER_COLUMNS: usize = 8;
+
+/// `PW_NODE_ACTIVATION_FINISHED`
+const STATUS_FINISHED: i32 = 3;
+
+/// Rate used for offline clocks so that values in the log, which are in
+/// microseconds, can be stored as-is in clock sample fields.
+const OFFLINE_RATE: Fraction = Fraction {
+    num: 1,
+    denom: 1_000_000,
+};
+
+struct Point {
+    period: i64,
+    end_date: i64,
+    delay: i64,
+    estimated: i64,
+}
+
+impl Point {
+    fn from_profiling(p: &Profiling) -> Self {
+        let denom = f64::from(p.clock.rate.denom);
+
+        Self {
+            period: (p.driver.signal - p.driver.prev_signal) / 1000,
+            end_date: (p.driver.finish - p.driver.signal) / 1000,
+            delay: (p.clock.delay as f64 * 1_000_000. / denom) as i64,
+            estimated: (p.clock.duration as f64 * 1_000_000. / (denom * p.clock.rate_diff)) as i64,
+        }
+    }
+}
+
+fn write_point(
+    out: &mut impl Write,
+    p: &Profiling,
+    followers: &mut Vec<(i32, String)>,
+) -> io::Result<()> {
+    let point = Point::from_profiling(p);
+
+    write!(
+        out,
+        "{}\t{}\t{}\t{}\t",
+        point.period.max(0),
+        point.end_date.max(0),
+        point.delay,
+        point.estimated
+    )?;
+
+    for follower in &p.followers {
+        if !followers.iter().any(|(id, _)| *id == follower.id) && followers.len() < MAX_FOLLOWERS {
+            followers.push((follower.id, follower.name.clone()));
+        }
+    }
+
+    for (id, _) in followers.iter() {
+        let Some(follower) = p.followers.iter().find(|f| f.id == *id) else {
+            out.write_all(" \t".repeat(FOLLOWER_COLUMNS).as_bytes())?;
+            continue;
+        };
+
+        let signal = (follower.signal - p.driver.signal) / 1000;
+        let awake = (follower.awake - p.driver.signal) / 1000;
+        let finish = (follower.finish - p.driver.signal) / 1000;
+
+        write!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t0\t",
+            follower.id,
+            signal.max(0),
+            awake.max(0),
+            finish.max(0),
+            if awake > 0 && signal > 0 && awake > signal {
+                awake - signal
+            } else {
+                0
+            },
+            if finish > 0 && awake > 0 && finish > awake {
+                finish - awake
+            } else {
+                0
+            },
+            follower.status
+        )?;
+    }
+
+    writeln!(out)
+}
+
+/// Writes a gnuplot script that plots the columns of the log.
+/// The Y axis is clamped to `y_max`, so that outliers don't flatten the rest of the plot.
+fn write_script(
+    out: &mut impl Write,
+    file: &str,
+    title: &str,
+    log_name: &str,
+    columns: &[(usize, String)],
+    y_max: Option<i64>,
+) -> io::Result<()> {
+    write!(
+        out,
+        "set output '{file}.svg'\n\
+         set terminal svg\n\
+         set grid\n\
+         set key right top\n\
+         set title \"{title}\"\n\
+         set xlabel \"audio cycles\"\n\
+         set ylabel \"usec\"\n"
+    )?;
+
+    if let Some(y_max) = y_max {
+        writeln!(out, "set yrange [*:{y_max}]")?;
+    }
+
+    out.write_all(b"plot ")?;
+
+    for (i, (column, title)) in columns.iter().enumerate() {
+        if i != 0 {
+            out.write_all(b", ")?;
+        }
+        write!(
+            out,
+            "\"{log_name}\" using {column} title \"{title}\" with lines"
+        )?;
+    }
+
+    out.write_all(b"\nunset output\n")
+}
+
+/// Writes gnuplot scripts like the ones `pw-profiler` generates
+fn write_scripts(
+    dir: &Path,
+    log_name: &str,
+    followers: &[(i32, String)],
+    y_max: Option<i64>,
+) -> io::Result<()> {
+    let plot = |file: &str, title: &str, columns: &[(usize, String)]| -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(dir.join(format!("{file}.plot")))?);
+        write_script(&mut out, file, title, log_name, columns, y_max)?;
+        out.flush()
+    };
+
+    let followers_columns = |offset: usize| {
+        std::iter::once((1, "Audio period".to_owned()))
+            .chain(followers.iter().enumerate().map(|(i, (id, name))| {
+                (
+                    DRIVER_COLUMNS + i * FOLLOWER_COLUMNS + offset,
+                    format!("{name}/{id}"),
+                )
+            }))
+            .collect::<Vec<_>>()
+    };
+
+    plot(
+        "Timing1",
+        "Audio driver timing",
+        &[
+            (3, "Audio driver delay".to_owned()),
+            (1, "Audio period".to_owned()),
+            (4, "Audio estimated".to_owned()),
+        ],
+    )?;
+    plot(
+        "Timing2",
+        "Driver end date",
+        &[(2, "Driver end date".to_owned())],
+    )?;
+    plot("Timing3", "Clients end date", &followers_columns(4))?;
+    plot(
+        "Timing4",
+        "Clients scheduling latency",
+        &followers_columns(5),
+    )?;
+    plot("Timing5", "Clients duration", &followers_columns(6))?;
+
+    let mut out = BufWriter::new(File::create(dir.join("generate_timings.sh"))?);
+    out.write_all(b"#!/bin/sh\n")?;
+    for i in 1..=5 {
+        writeln!(out, "gnuplot Timing{i}.plot")?;
+    }
+    out.flush()
+}
+
+/// Records samples of a single driver to a `pw-profiler` compatible log
+pub struct Writer {
+    output: BufWriter<File>,
+    path: PathBuf,
+
+    /// The driver whose samples are logged. If [`None`], the first driver
+    /// that appears will be used.
+    driver_id: Option<i32>,
+
+    /// Follower slots in the order of their columns
+    followers: Vec<(i32, String)>,
+
+    samples: usize,
+    /// Longest estimated period, the plots are clamped to a multiple of it
+    max_estimated: i64,
+    /// Whether the scripts were written with [`Self::finish`], otherwise they're written on drop
+    finished: bool,
+}
+
+impl Writer {
+    pub fn create(path: impl Into<PathBuf>, driver_id: Option<i32>) -> io::Result<Self> {
+        let path = path.into();
+
+        let writer = Self {
+            output: BufWriter::new(File::create(&path)?),
+            path,
+            driver_id,
+            followers: Vec::new(),
+            samples: 0,
+            max_estimated: 0,
+            finished: false,
+        };
+
+        // Have scripts next to the log even if the recording doesn't stop normally
+        writer.write_scripts()?;
+
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, profiling: &Profiling) -> io::Result<()> {
+        let driver_id = *self.driver_id.get_or_insert(profiling.driver.id);
+
+        if profiling.driver.id != driver_id {
+            return Ok(());
+        }
+
+        self.samples += 1;
+        self.max_estimated = self
+            .max_estimated
+            .max(Point::from_profiling(profiling).estimated);
+
+        let followers = self.followers.len();
+
+        write_point(&mut self.output, profiling, &mut self.followers)?;
+
+        // Keep the names of new followers in the scripts
+        if self.followers.len() != followers {
+            self.write_scripts()?;
+        }
+
+        Ok(())
+    }
+
+    fn write_scripts(&self) -> io::Result<()> {
+        let dir = self.path.parent().unwrap_or_else(|| Path::new(""));
+        let log_name = self
+            .path
+            .file_name()
+            .map_or_else(|| "profiler.log".into(), |n| n.to_string_lossy());
+
+        // Values past a few periods are xruns or glitches
+        let y_max = (self.max_estimated > 0).then_some(self.max_estimated * 3);
+
+        write_scripts(dir, &log_name, &self.followers, y_max)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub const fn driver_id(&self) -> Option<i32> {
+        self.driver_id
+    }
+
+    pub const fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Flushes the log and writes the gnuplot scripts next to it
+    pub fn finish(mut self) -> io::Result<()> {
+        self.finished = true;
+
+        self.output.flush()?;
+        self.write_scripts()
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        if !self.finished {
+            // There's nowhere to report errors to
+            let _ = self.output.flush();
+            let _ = self.write_scripts();
+        }
+    }
+}
+
+/// Extracts follower names from the titles in a `Timing3.plot` script
+fn read_follower_names(script: impl BufRead) -> Vec<(usize, String)> {
+    let mut names = Vec::new();
+
+    for line in script.lines().map_while(Result::ok) {
+        for plot in line.split(", ") {
+            let Some((_, rest)) = plot.split_once(" using ") else {
+                continue;
+            };
+            let Some((column, rest)) = rest.split_once(' ') else {
+                continue;
+            };
+            let Some(column) = column
+                .split(':')
+                .next()
+                .and_then(|c| c.parse::<usize>().ok())
+            else {
+                continue;
+            };
+
+            // First column of a slot is 1 + DRIVER_COLUMNS + i * FOLLOWER_COLUMNS,
+            // Timing3 plots the 4th column of each slot
+            let Some(slot) = column
+                .checked_sub(DRIVER_COLUMNS + 4)
+                .filter(|c| c % FOLLOWER_COLUMNS == 0)
+                .map(|c| c / FOLLOWER_COLUMNS)
+            else {
+                continue;
+            };
+
+            let Some(title) = rest
+                .split_once("title \"")
+                .and_then(|(_, t)| t.split_once('"'))
+                .map(|(t, _)| t)
+            else {
+                continue;
+            };
+
+            // Titles are "name/id"
+            let name = title.rsplit_once('/').map_or(title, |(name, _)| name);
+
+            names.push((slot, name.to_owned()));
+        }
+    }
+
+    names
+}
+
+fn parse(
+    log: impl BufRead,
+    driver_id: i32,
+    driver_name: &str,
+    follower_names: &[(usize, String)],
+) -> io::Result<Vec<Profiling>> {
+    fn invalid(line: usize, what: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Line {line}: invalid {what}"),
+        )
+    }
+
+    /// Time that is `micros` after `nsec`, if it fits
+    fn after(nsec: i64, micros: i64) -> Option<i64> {
+        micros.checked_mul(1000)?.checked_add(nsec)
+    }
+
+    let mut profilings = Vec::new();
+
+    // Time of the current cycle's signal in nanoseconds, relative to the start of the log
+    let mut signal = 0i64;
+    let mut position = 0i64;
+
+    for (i, line) in log.lines().enumerate() {
+        let line = line?;
+        let n = i + 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split('\t').collect();
+        if columns.len() < DRIVER_COLUMNS {
+            return Err(invalid(n, "driver columns"));
+        }
+
+        let mut driver = [0i64; DRIVER_COLUMNS];
+        for (value, column) in driver.iter_mut().zip(&columns) {
+            *value = column
+                .trim()
+                .parse()
+                .map_err(|_| invalid(n, "driver value"))?;
+        }
+        let [period, end_date, delay, estimated] = driver;
+
+        if !profilings.is_empty() {
+            signal = after(signal, period).ok_or_else(|| invalid(n, "period"))?;
+        }
+        let prev_signal = period
+            .checked_mul(1000)
+            .and_then(|period| signal.checked_sub(period))
+            .ok_or_else(|| invalid(n, "period"))?;
+        let finish = after(signal, end_date).ok_or_else(|| invalid(n, "end date"))?;
+        let next_nsec = after(signal, estimated).ok_or_else(|| invalid(n, "estimated"))?;
+
+        let mut followers = Vec::new();
+
+        for (slot, columns) in columns[DRIVER_COLUMNS..]
+            .chunks_exact(FOLLOWER_COLUMNS)
+            .enumerate()
+        {
+            if columns[0].trim().is_empty() {
+                continue;
+            }
+
+            let mut values = [0i64; FOLLOWER_COLUMNS];
+            for (value, column) in values.iter_mut().zip(columns) {
+                *value = column
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid(n, "follower value"))?;
+            }
+            let [id, f_signal, awake, f_finish, _, _, status, _] = values;
+
+            let times = [f_signal, awake, f_finish].map(|micros| after(signal, micros));
+            let [Some(f_signal), Some(awake), Some(f_finish)] = times else {
+                return Err(invalid(n, "follower time"));
+            };
+
+            followers.push(NodeBlock {
+                id: id as i32,
+                name: follower_names
+                    .iter()
+                    .find_map(|(s, name)| (*s == slot).then(|| name.clone()))
+                    .unwrap_or_default(),
+                prev_signal,
+                signal: f_signal,
+                awake,
+                finish: f_finish,
+                status: status as i32,
+                latency: Fraction { num: 0, denom: 0 },
+                xrun_count: None,
+            });
+        }
+
+        profilings.push(Profiling {
+            info: Info {
+                counter: profilings.len() as i64,
+                cpu_load_fast: 0.,
+                cpu_load_medium: 0.,
+                cpu_load_slow: 0.,
+                xrun_count: 0,
+            },
+            clock: Clock {
+                flags: 0,
+                id: driver_id,
+                name: driver_name.to_owned(),
+                nsec: signal,
+                rate: OFFLINE_RATE,
+                position,
+                duration: estimated,
+                delay,
+                rate_diff: 1.,
+                next_nsec,
+                transport_state: None,
+                cycle: None,
+                xrun_duration: None,
+            },
+            driver: NodeBlock {
+                id: driver_id,
+                name: driver_name.to_owned(),
+                prev_signal,
+                signal,
+                awake: signal,
+                finish,
+                status: STATUS_FINISHED,
+                latency: Fraction { num: 0, denom: 0 },
+                xrun_count: None,
+            },
+            followers,
+        });
+
+        position = position
+            .checked_add(estimated)
+            .ok_or_else(|| invalid(n, "estimated"))?;
+    }
+
+    Ok(profilings)
+}
+
+/// Reads a `pw-profiler` log as samples of an offline driver with the provided ID.
+/// Follower names are taken from the `Timing3.plot` script next to the log, if it exists.
+pub fn read(path: &Path, driver_id: i32) -> io::Result<Vec<Profiling>> {
+    let follower_names = path
+        .parent()
+        .and_then(|dir| File::open(dir.join("Timing3.plot")).ok())
+        .map(|f| read_follower_names(BufReader::new(f)))
+        .unwrap_or_default();
+
+    let driver_name = path
+        .file_name()
+        .map_or_else(|| "profiler.log".into(), |n| n.to_string_lossy());
+
+    parse(
+        BufReader::new(File::open(path)?),
+        driver_id,
+        &driver_name,
+        &follower_names,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn profiling(signal: i64, followers: Vec<NodeBlock>) -> Profiling {
+        let mut p = Profiling::test(0, signal, followers);
+        p.clock.delay = 96;
+        p
+    }
+
+    #[test]
+    fn roundtrip() {
+        let samples = [
+            profiling(
+                1_000_000,
+                vec![
+                    NodeBlock::test(40, 1_010_000, 1_020_000, 1_050_000),
+                    NodeBlock::test(41, 1_050_000, 1_060_000, 1_100_000),
+                ],
+            ),
+            profiling(
+                2_000_000,
+                vec![NodeBlock::test(41, 2_010_000, 2_020_000, 2_030_000)],
+            ),
+        ];
+
+        let mut log = Vec::new();
+        let mut followers = Vec::new();
+        for p in &samples {
+            write_point(&mut log, p, &mut followers).unwrap();
+        }
+
+        let text = String::from_utf8(log.clone()).unwrap();
+        let second = text.lines().nth(1).unwrap();
+        assert_eq!(second.split('\t').nth(DRIVER_COLUMNS), Some(" "));
+
+        let parsed = parse(log.as_slice(), -1, "test", &[(1, "named".to_owned())]).unwrap();
+        assert_eq!(parsed.len(), 2);
+
+        assert_eq!(parsed[0].clock.delay, 2000);
+        assert_eq!(parsed[0].clock.duration, 1000);
+        assert_eq!(parsed[0].driver.finish - parsed[0].driver.signal, 200_000);
+        assert_eq!(parsed[1].driver.signal - parsed[0].driver.signal, 1_000_000);
+
+        assert_eq!(parsed[0].followers.len(), 2);
+        assert_eq!(parsed[1].followers.len(), 1);
+
+        let f = &parsed[1].followers[0];
+        assert_eq!(f.id, 41);
+        assert_eq!(f.name, "named");
+        assert_eq!(f.awake - f.signal, 10_000);
+        assert_eq!(f.finish - parsed[1].driver.signal, 30_000);
+    }
+
+    #[test]
+    fn overflowing_values() {
+        let log = format!("1000\t{}\t0\t1000\n", i64::MAX / 10);
+        assert!(parse(log.as_bytes(), -1, "test", &[]).is_err());
+
+        let log = format!("1000\t0\t0\t1000\n{}\t0\t0\t1000\n", i64::MAX / 100);
+        assert!(parse(log.as_bytes(), -1, "test", &[]).is_err());
+    }
+
+    #[test]
+    fn script_range() {
+        let mut script = Vec::new();
+        write_script(
+            &mut script,
+            "Timing2",
+            "Driver end date",
+            "profiler.log",
+            &[(2, "Driver end date".to_owned())],
+            Some(3000),
+        )
+        .unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("set yrange [*:3000]\nplot \"profiler.log\" using 2"));
+
+        let mut script = Vec::new();
+        write_script(&mut script, "Timing2", "", "profiler.log", &[], None).unwrap();
+        assert!(!String::from_utf8(script).unwrap().contains("yrange"));
+    }
+
+    #[test]
+    fn follower_names() {
+        let script = "plot \"profiler.log\" using 1 title \"Audio period\" with lines, \
+                      \"profiler.log\" using 8 title \"alsa/a/b/40\" with lines, \
+                      \"profiler.log\" using 16 title \"other/41\" with lines\n";
+
+        assert_eq!(
+            read_follower_names(script.as_bytes()),
+            vec![(0, "alsa/a/b".to_owned()), (1, "other".to_owned())]
+        );
+    }
+}
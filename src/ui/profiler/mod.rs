@@ -0,0 +1,3864 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+mod baseline;
+mod histogram;
+mod lod;
+mod log;
+mod stats;
+mod trigger;
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, hash_map::Entry},
+    path::Path,
+    rc::{Rc, Weak},
+};
+
+use eframe::egui;
+use egui_plot::{self, Plot, PlotPoints};
+
+use crate::{
+    backend::{
+        self, Request,
+        pods::profiler::{NodeBlock, Profiling},
+    },
+    ui::{
+        globals_store::Global,
+        util::{
+            RingBuf,
+            persistence::PersistentView,
+            uis::{KvMatcher, global_info_button},
+        },
+    },
+};
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+mod data {
+    use std::{
+        cell::{OnceCell, RefCell},
+        collections::{BTreeMap, HashMap, btree_map::Entry},
+        rc::Weak,
+    };
+
+    use egui_plot::{PlotPoint, PlotPoints};
+
+    use crate::{
+        backend::pods::profiler::{NodeBlock, Profiling},
+        ui::{globals_store::Global, util::RingBuf},
+    };
+
+    use super::{
+        lod::Column,
+        stats::{Stats, Window},
+    };
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum XAxis {
+        Cycle,
+        Time,
+    }
+
+    impl XAxis {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Cycle => "Cycle",
+                Self::Time => "Time",
+            }
+        }
+    }
+
+    pub struct Positions<'a> {
+        axis: XAxis,
+        nsec: &'a RingBuf<i64>,
+        /// 1 where samples are missing before the sample, 0 otherwise
+        gaps: &'a Column,
+        origin: i64,
+    }
+
+    impl Positions<'_> {
+        pub const fn axis(&self) -> XAxis {
+            self.axis
+        }
+
+        pub fn len(&self) -> usize {
+            self.nsec.len()
+        }
+
+        fn time(&self, sample: usize) -> f64 {
+            self.nsec.get(sample).map_or(f64::NAN, |&nsec| {
+                (nsec - self.origin) as f64 / 1_000_000_000.
+            })
+        }
+
+        pub fn x(&self, sample: usize) -> f64 {
+            match self.axis {
+                XAxis::Cycle => sample as f64,
+                XAxis::Time => self.time(sample),
+            }
+        }
+
+        fn gap(&self, start: usize, end: usize) -> bool {
+            self.gaps.min_max(start, end).1 > 0.
+        }
+
+        pub fn max_x(&self) -> f64 {
+            self.len().checked_sub(1).map_or(0., |last| self.x(last))
+        }
+
+        fn partition_point(&self, x: f64) -> usize {
+            let (mut low, mut high) = (0, self.len());
+
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if self.x(mid) < x {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+
+            low
+        }
+
+        pub fn nearest(&self, x: f64) -> Option<(usize, f64)> {
+            let len = self.len();
+            if len == 0 {
+                return None;
+            }
+
+            let sample = match self.axis {
+                XAxis::Cycle => x.round().max(0.) as usize,
+                XAxis::Time => {
+                    let after = self.partition_point(x);
+                    if after > 0 && (after == len || x - self.x(after - 1) < self.x(after) - x) {
+                        after - 1
+                    } else {
+                        after
+                    }
+                }
+            }
+            .min(len - 1);
+
+            Some((sample, self.time(sample)))
+        }
+
+        /// The samples that are between the X positions, plus one on each side
+        /// so that lines going off the edges are drawn
+        pub fn span(&self, min_x: f64, max_x: f64, columns: usize) -> Span {
+            Span {
+                start: self.partition_point(min_x).saturating_sub(1),
+                end: (self.partition_point(max_x) + 1).min(self.len()),
+                columns,
+            }
+        }
+
+        pub fn full_span(&self, columns: usize) -> Span {
+            Span {
+                start: 0,
+                end: self.len(),
+                columns,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct Span {
+        start: usize,
+        end: usize,
+        columns: usize,
+    }
+
+    /// Plot points of the samples in the span.
+    /// When there are more samples than columns only the minimum and maximum
+    /// of the samples of each column are plotted, which looks the same.
+    fn generate_plot_points(
+        positions: &Positions,
+        span: Span,
+        value: impl Fn(usize) -> f64,
+        min_max: impl Fn(usize, usize) -> (f64, f64),
+    ) -> PlotPoints<'static> {
+        let end = span.end.min(positions.len());
+        let start = span.start.min(end);
+        let samples = end - start;
+        let columns = span.columns.max(1);
+
+        let break_on_gaps = positions.axis == XAxis::Time;
+
+        let mut plot_points = Vec::with_capacity(samples.min(columns * 3));
+
+        if samples <= columns * 2 {
+            for i in start..end {
+                let x = positions.x(i);
+
+                // Break the line where samples are missing
+                if break_on_gaps && positions.gap(i, i + 1) {
+                    plot_points.push(PlotPoint { x, y: f64::NAN });
+                }
+
+                plot_points.push(PlotPoint { x, y: value(i) });
+            }
+        } else {
+            for column in 0..columns {
+                let a = start + samples * column / columns;
+                let b = start + samples * (column + 1) / columns;
+
+                let x = positions.x(a);
+
+                if break_on_gaps && positions.gap(a, b) {
+                    plot_points.push(PlotPoint { x, y: f64::NAN });
+                }
+
+                let (min, max) = min_max(a, b);
+                plot_points.push(PlotPoint { x, y: min });
+                plot_points.push(PlotPoint { x, y: max });
+            }
+        }
+
+        PlotPoints::Owned(plot_points)
+    }
+
+    fn column_plot_points(
+        positions: &Positions,
+        span: Span,
+        column: &Column,
+    ) -> PlotPoints<'static> {
+        generate_plot_points(
+            positions,
+            span,
+            |i| column.get(i).copied().unwrap_or(f64::NAN),
+            |a, b| column.min_max(a, b),
+        )
+    }
+
+    fn last_n(buf: &Column, n: usize) -> impl Iterator<Item = f64> {
+        buf.iter().skip(buf.len().saturating_sub(n)).copied()
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum NodeMetric {
+        EndDate,
+        SchedulingLatency,
+        Duration,
+    }
+
+    impl NodeMetric {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::EndDate => "End Date",
+                Self::SchedulingLatency => "Scheduling Latency",
+                Self::Duration => "Duration",
+            }
+        }
+
+        fn select<'a>(
+            self,
+            end_date: &'a Column,
+            scheduling_latency: &'a Column,
+            duration: &'a Column,
+        ) -> &'a Column {
+            match self {
+                Self::EndDate => end_date,
+                Self::SchedulingLatency => scheduling_latency,
+                Self::Duration => duration,
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct NodeStats {
+        pub end_date: Option<Stats>,
+        pub scheduling_latency: Option<Stats>,
+        pub duration: Option<Stats>,
+    }
+
+    impl NodeStats {
+        fn calculate(
+            end_date: &Column,
+            scheduling_latency: &Column,
+            duration: &Column,
+            n: usize,
+        ) -> Self {
+            Self {
+                end_date: Stats::calculate(last_n(end_date, n)),
+                scheduling_latency: Stats::calculate(last_n(scheduling_latency, n)),
+                duration: Stats::calculate(last_n(duration, n)),
+            }
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = (&'static str, Option<&Stats>)> {
+            [
+                ("End Date", self.end_date.as_ref()),
+                ("Scheduling Latency", self.scheduling_latency.as_ref()),
+                ("Duration", self.duration.as_ref()),
+            ]
+            .into_iter()
+        }
+    }
+
+    #[derive(Default)]
+    pub struct DriverStats {
+        pub delay: Option<Stats>,
+        pub period: Option<Stats>,
+        pub estimated: Option<Stats>,
+        pub node: NodeStats,
+    }
+
+    impl DriverStats {
+        pub fn iter(&self) -> impl Iterator<Item = (&'static str, Option<&Stats>)> {
+            [
+                ("Delay", self.delay.as_ref()),
+                ("Period", self.period.as_ref()),
+                ("Estimated", self.estimated.as_ref()),
+            ]
+            .into_iter()
+            .chain(self.node.iter())
+        }
+    }
+
+    /// When a node was signaled, woke up and finished, in microseconds since the driver was signaled
+    pub struct NodeSpan<'a> {
+        pub name: &'a str,
+        pub signal: f64,
+        pub awake: f64,
+        pub finish: f64,
+    }
+
+    impl NodeSpan<'_> {
+        fn from_measurements(
+            name: &str,
+            end_date: f64,
+            scheduling_latency: f64,
+            duration: f64,
+        ) -> Option<NodeSpan<'_>> {
+            // Didn't participate in the cycle
+            if end_date.is_nan() {
+                return None;
+            }
+
+            let awake = end_date - duration;
+
+            Some(NodeSpan {
+                name,
+                signal: awake - scheduling_latency,
+                awake,
+                finish: end_date,
+            })
+        }
+    }
+
+    pub struct CycleSpans<'a> {
+        pub quantum: f64,
+        pub time: f64,
+        pub nodes: Vec<NodeSpan<'a>>,
+    }
+
+    struct ClientMeasurements {
+        end_date: Column,
+        scheduling_latency: Column,
+        duration: Column,
+    }
+
+    impl ClientMeasurements {
+        fn with_max_profilings(max: usize) -> Self {
+            Self {
+                end_date: Column::with_capacity(max),
+                scheduling_latency: Column::with_capacity(max),
+                duration: Column::with_capacity(max),
+            }
+        }
+
+        fn len(&self) -> usize {
+            assert!(
+                self.end_date.len() == self.scheduling_latency.len()
+                    && self.scheduling_latency.len() == self.duration.len()
+            );
+
+            self.end_date.len()
+        }
+
+        fn values(&self, metric: NodeMetric) -> impl Iterator<Item = f64> {
+            metric
+                .select(&self.end_date, &self.scheduling_latency, &self.duration)
+                .iter()
+                .copied()
+        }
+
+        fn span<'a>(&self, name: &'a str, sample: usize) -> Option<NodeSpan<'a>> {
+            NodeSpan::from_measurements(
+                name,
+                *self.end_date.get(sample)?,
+                *self.scheduling_latency.get(sample)?,
+                *self.duration.get(sample)?,
+            )
+        }
+
+        fn add_empty(&mut self, max: usize) {
+            self.end_date.push_back(max, f64::NAN);
+            self.scheduling_latency.push_back(max, f64::NAN);
+            self.duration.push_back(max, f64::NAN);
+        }
+
+        fn push(&mut self, max: usize, follower: &NodeBlock, driver: &NodeBlock) {
+            let end_date = (follower.finish - driver.signal) as f64 / 1000.;
+            let scheduling_latency = (follower.awake - follower.signal) as f64 / 1000.;
+            let duration = (follower.finish - follower.awake) as f64 / 1000.;
+
+            self.end_date.push_back(max, end_date);
+            self.scheduling_latency.push_back(max, scheduling_latency);
+            self.duration.push_back(max, duration);
+        }
+
+        fn adjust_queues(&mut self, max: usize) {
+            self.end_date.resize(max);
+            self.scheduling_latency.resize(max);
+            self.duration.resize(max);
+        }
+    }
+
+    #[derive(Clone, Copy, Default)]
+    pub struct Resampling {
+        /// The last rate of the follower that differed from the rate of the driver
+        pub rate: u32,
+        pub resampled: usize,
+        pub cycles: usize,
+    }
+
+    pub struct Client {
+        last_profiling: Option<NodeBlock>,
+
+        name: String,
+        title: String,
+        measurements: ClientMeasurements,
+        // Calculated when first needed after new measurements arrive
+        stats: OnceCell<NodeStats>,
+        resampling: Resampling,
+
+        // Position of last non-empty profiling that was added.
+        // When this reaches 0 every profiling is empty indicating
+        // that this follower has no statistics to show
+        last_non_empty_pos: usize,
+
+        // Stored weakly as these objects live for as long as there
+        // are stored profilings of them, which can be longer than
+        // the lifetime of the global
+        pub global: Weak<RefCell<Global>>,
+    }
+
+    impl Client {
+        /// `offset` is the number of measurements the driver already has.
+        /// The measurements are padded accordingly so that they line up with the driver's.
+        fn new(
+            node: &NodeBlock,
+            max_profilings: usize,
+            offset: usize,
+            global: Weak<RefCell<Global>>,
+        ) -> Self {
+            let mut measurements = ClientMeasurements::with_max_profilings(max_profilings);
+            for _ in 0..offset {
+                measurements.add_empty(max_profilings);
+            }
+
+            Self {
+                last_profiling: None,
+
+                name: node.name.clone(),
+                title: format!("{}/{}", node.name, node.id),
+                measurements,
+                stats: OnceCell::new(),
+                resampling: Resampling::default(),
+
+                last_non_empty_pos: max_profilings,
+
+                global,
+            }
+        }
+
+        pub fn title(&self) -> &str {
+            &self.title
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn add_measurement(
+            &mut self,
+            follower: &NodeBlock,
+            driver: &NodeBlock,
+            rate: u32,
+            max_profilings: usize,
+        ) {
+            self.measurements.push(max_profilings, follower, driver);
+
+            self.resampling.cycles += 1;
+            if follower.latency.denom != 0 && follower.latency.denom != rate {
+                self.resampling.rate = follower.latency.denom;
+                self.resampling.resampled += 1;
+            }
+
+            self.last_profiling = Some(follower.clone());
+            self.last_non_empty_pos = self.measurements.len();
+        }
+
+        fn add_empty_measurement(&mut self, max_profilings: usize) {
+            self.measurements.add_empty(max_profilings);
+
+            self.last_non_empty_pos -= 1;
+
+            self.last_profiling = None;
+        }
+
+        const fn is_empty(&self) -> bool {
+            self.last_non_empty_pos == 0
+        }
+
+        pub const fn last_profiling(&self) -> Option<&NodeBlock> {
+            self.last_profiling.as_ref()
+        }
+
+        pub const fn resampling(&self) -> &Resampling {
+            &self.resampling
+        }
+
+        pub fn end_date(&self, positions: &Positions, span: Span) -> PlotPoints<'static> {
+            column_plot_points(positions, span, &self.measurements.end_date)
+        }
+        pub fn scheduling_latency(&self, positions: &Positions, span: Span) -> PlotPoints<'static> {
+            column_plot_points(positions, span, &self.measurements.scheduling_latency)
+        }
+        pub fn duration(&self, positions: &Positions, span: Span) -> PlotPoints<'static> {
+            column_plot_points(positions, span, &self.measurements.duration)
+        }
+
+        /// All retained values of the metric, [`f64::NAN`] where the client didn't participate
+        pub fn values(&self, metric: NodeMetric) -> impl Iterator<Item = f64> {
+            self.measurements.values(metric)
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum DriverMetric {
+        Delay,
+        Period,
+        Estimated,
+        EndDate,
+        /// Difference of the rate of the driver's clock from the system clock, in ppm
+        RateDiff,
+        Jitter,
+    }
+
+    impl DriverMetric {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Delay => "Delay",
+                Self::Period => "Period",
+                Self::Estimated => "Estimated",
+                Self::EndDate => "End Date",
+                Self::RateDiff => "Rate Difference",
+                Self::Jitter => "Period Jitter",
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Normalization {
+        None,
+        Quantum,
+        Rate,
+    }
+
+    impl Normalization {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::None => "Time",
+                Self::Quantum => "Quantum",
+                Self::Rate => "Rate",
+            }
+        }
+
+        pub fn format(self, value: f64) -> String {
+            match self {
+                Self::None => format!("{value:.3}us"),
+                Self::Quantum => format!("{value:.3} of the quantum"),
+                Self::Rate => format!("{value:.1} samples"),
+            }
+        }
+    }
+
+    struct DriverMeasurements {
+        nsec: RingBuf<i64>,
+        gaps: Column,
+        quantum: RingBuf<f64>,
+        /// Microseconds per sample of the driver's clock
+        sample_duration: RingBuf<f64>,
+        delay: Column,
+        period: Column,
+        estimated: Column,
+        end_date: Column,
+        rate_diff: Column,
+        jitter: Column,
+        scheduling_latency: Column,
+        duration: Column,
+
+        // Kept up to date as samples arrive for each metric and normalization that was plotted,
+        // so that they are plotted using their pyramid
+        normalized: RefCell<HashMap<(DriverMetric, Normalization), Column>>,
+    }
+
+    impl DriverMeasurements {
+        fn with_max_profilings(max: usize) -> Self {
+            Self {
+                nsec: RingBuf::with_capacity(max),
+                gaps: Column::with_capacity(max),
+                quantum: RingBuf::with_capacity(max),
+                sample_duration: RingBuf::with_capacity(max),
+                delay: Column::with_capacity(max),
+                period: Column::with_capacity(max),
+                estimated: Column::with_capacity(max),
+                end_date: Column::with_capacity(max),
+                rate_diff: Column::with_capacity(max),
+                jitter: Column::with_capacity(max),
+                scheduling_latency: Column::with_capacity(max),
+                duration: Column::with_capacity(max),
+
+                normalized: RefCell::new(HashMap::new()),
+            }
+        }
+
+        const fn column(&self, metric: DriverMetric) -> &Column {
+            match metric {
+                DriverMetric::Delay => &self.delay,
+                DriverMetric::Period => &self.period,
+                DriverMetric::Estimated => &self.estimated,
+                DriverMetric::EndDate => &self.end_date,
+                DriverMetric::RateDiff => &self.rate_diff,
+                DriverMetric::Jitter => &self.jitter,
+            }
+        }
+
+        fn plot_points(
+            &self,
+            metric: DriverMetric,
+            normalization: Normalization,
+            positions: &Positions,
+            span: Span,
+        ) -> PlotPoints<'static> {
+            if normalization == Normalization::None {
+                return column_plot_points(positions, span, self.column(metric));
+            }
+
+            let mut normalized = self.normalized.borrow_mut();
+
+            // Normalize all of the samples the first time the values are plotted
+            let column = normalized
+                .entry((metric, normalization))
+                .or_insert_with(|| {
+                    let mut column = Column::with_capacity(self.len());
+                    for i in 0..self.len() {
+                        column
+                            .push_back(self.len(), self.normalized_value(metric, normalization, i));
+                    }
+                    column
+                });
+
+            column_plot_points(positions, span, column)
+        }
+
+        fn normalized_value(
+            &self,
+            metric: DriverMetric,
+            normalization: Normalization,
+            sample: usize,
+        ) -> f64 {
+            let divisor = match normalization {
+                Normalization::None => {
+                    return self.column(metric).get(sample).copied().unwrap_or(f64::NAN);
+                }
+                Normalization::Quantum => &self.quantum,
+                Normalization::Rate => &self.sample_duration,
+            };
+
+            match (self.column(metric).get(sample), divisor.get(sample)) {
+                (Some(v), Some(divisor)) => v / divisor,
+                _ => f64::NAN,
+            }
+        }
+
+        /// `origin` is the time X starts from, or the first sample if [`None`]
+        fn positions(&self, axis: XAxis, origin: Option<i64>) -> Positions<'_> {
+            Positions {
+                axis,
+                nsec: &self.nsec,
+                gaps: &self.gaps,
+                origin: origin
+                    .or_else(|| self.nsec.iter().next().copied())
+                    .unwrap_or_default(),
+            }
+        }
+
+        fn node_values(&self, metric: NodeMetric) -> impl Iterator<Item = f64> {
+            metric
+                .select(&self.end_date, &self.scheduling_latency, &self.duration)
+                .iter()
+                .copied()
+        }
+
+        fn len(&self) -> usize {
+            self.end_date.len()
+        }
+
+        fn window_len(&self, window: Window) -> usize {
+            match window {
+                Window::All => self.len(),
+                Window::Cycles(n) => n.min(self.len()),
+                Window::Seconds(secs) => {
+                    let Some(&last) = self.nsec.iter().last() else {
+                        return 0;
+                    };
+                    let start = last - (secs * 1_000_000_000.) as i64;
+
+                    self.nsec.iter().filter(|&&nsec| nsec >= start).count()
+                }
+            }
+        }
+
+        fn push(&mut self, max: usize, p: &Profiling) {
+            let delay = (p.clock.delay * 1_000_000) as f64 / f64::from(p.clock.rate.denom);
+
+            let period = (p.driver.signal - p.driver.prev_signal) as f64 / 1000.;
+
+            let estimated = (p.clock.duration * 1_000_000) as f64
+                / (p.clock.rate_diff * f64::from(p.clock.rate.denom));
+
+            let end_date = (p.driver.finish - p.driver.signal) as f64 / 1000.;
+
+            let scheduling_latency = (p.driver.awake - p.driver.signal) as f64 / 1000.;
+            let duration = (p.driver.finish - p.driver.awake) as f64 / 1000.;
+
+            let quantum = (p.clock.duration * 1_000_000) as f64 * f64::from(p.clock.rate.num)
+                / f64::from(p.clock.rate.denom);
+
+            let sample_duration = 1_000_000. * f64::from(p.clock.rate.num)
+                / (p.clock.rate_diff * f64::from(p.clock.rate.denom));
+
+            let rate_diff = (p.clock.rate_diff - 1.) * 1_000_000.;
+            let jitter = period - quantum;
+
+            // More time than a cycle passed since the previous sample
+            let gap = self
+                .nsec
+                .len()
+                .checked_sub(1)
+                .and_then(|last| self.nsec.get(last))
+                .is_some_and(|&prev| (p.clock.nsec - prev) as f64 > period * 1500.);
+
+            self.gaps.push_back(max, if gap { 1. } else { 0. });
+            self.nsec.push_back(max, p.clock.nsec);
+            self.quantum.push_back(max, quantum);
+            self.sample_duration.push_back(max, sample_duration);
+            self.delay.push_back(max, delay);
+            self.period.push_back(max, period);
+            self.estimated.push_back(max, estimated);
+            self.end_date.push_back(max, end_date);
+            self.rate_diff.push_back(max, rate_diff);
+            self.jitter.push_back(max, jitter);
+            self.scheduling_latency.push_back(max, scheduling_latency);
+            self.duration.push_back(max, duration);
+
+            let last = self.len() - 1;
+            let mut normalized = self.normalized.take();
+            for (&(metric, normalization), column) in &mut normalized {
+                column.push_back(max, self.normalized_value(metric, normalization, last));
+            }
+            *self.normalized.get_mut() = normalized;
+        }
+
+        fn clear(&mut self) {
+            self.normalized.get_mut().clear();
+            self.gaps.clear();
+            self.nsec.clear();
+            self.quantum.clear();
+            self.sample_duration.clear();
+            self.delay.clear();
+            self.period.clear();
+            self.estimated.clear();
+            self.end_date.clear();
+            self.rate_diff.clear();
+            self.jitter.clear();
+            self.scheduling_latency.clear();
+            self.duration.clear();
+        }
+
+        fn adjust_queues(&mut self, max: usize) {
+            for column in self.normalized.get_mut().values_mut() {
+                column.resize(max);
+            }
+            self.gaps.resize(max);
+            self.nsec.resize(max);
+            self.quantum.resize(max);
+            self.sample_duration.resize(max);
+            self.delay.resize(max);
+            self.period.resize(max);
+            self.estimated.resize(max);
+            self.end_date.resize(max);
+            self.rate_diff.resize(max);
+            self.jitter.resize(max);
+            self.scheduling_latency.resize(max);
+            self.duration.resize(max);
+        }
+
+        fn stats(&self, n: usize) -> DriverStats {
+            DriverStats {
+                delay: Stats::calculate(last_n(&self.delay, n)),
+                period: Stats::calculate(last_n(&self.period, n)),
+                estimated: Stats::calculate(last_n(&self.estimated, n)),
+                node: NodeStats::calculate(
+                    &self.end_date,
+                    &self.scheduling_latency,
+                    &self.duration,
+                    n,
+                ),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    pub enum XrunReason {
+        XrunCount,
+        NotCompleted,
+        LateFinish,
+        /// No node stood out, this one was the busiest
+        LongestBusy,
+    }
+
+    impl XrunReason {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::XrunCount => "Xrun count increased",
+                Self::NotCompleted => "Did not complete",
+                Self::LateFinish => "Finished after the quantum",
+                Self::LongestBusy => "Longest busy",
+            }
+        }
+    }
+
+    pub struct Xrun {
+        /// Index of the xrun cycle counting all profilings ever added to the driver
+        sample: usize,
+
+        pub culprit: Option<(NodeBlock, XrunReason)>,
+
+        pub before: Profiling,
+        pub cycle: Profiling,
+        pub after: Option<Profiling>,
+    }
+
+    impl Xrun {
+        pub fn detect(prev: &Profiling, p: &Profiling, sample: usize) -> Option<Self> {
+            let xrun_count_increased = |nb: &NodeBlock| {
+                std::iter::once(&prev.driver)
+                    .chain(&prev.followers)
+                    .find(|prev_nb| prev_nb.id == nb.id)
+                    .and_then(|prev_nb| prev_nb.xrun_count)
+                    .zip(nb.xrun_count)
+                    .is_some_and(|(prev, current)| current > prev)
+            };
+
+            let nodes = || std::iter::once(&p.driver).chain(&p.followers);
+
+            if p.info.xrun_count <= prev.info.xrun_count && !nodes().any(xrun_count_increased) {
+                return None;
+            }
+
+            let busy = |nb: &&NodeBlock| nb.finish - nb.awake;
+
+            let quantum = (p.clock.duration as f64 * f64::from(p.clock.rate.num)
+                / f64::from(p.clock.rate.denom)
+                * 1_000_000_000.) as i64;
+
+            let culprit = nodes()
+                .filter(|nb| xrun_count_increased(nb))
+                .max_by_key(busy)
+                .map(|nb| (nb, XrunReason::XrunCount))
+                .or_else(|| {
+                    p.followers
+                        .iter()
+                        .find(|nb| nb.signal > nb.prev_signal && nb.finish < nb.awake)
+                        .map(|nb| (nb, XrunReason::NotCompleted))
+                })
+                .or_else(|| {
+                    p.followers
+                        .iter()
+                        .filter(|nb| nb.finish - p.driver.signal > quantum)
+                        .max_by_key(|nb| nb.finish)
+                        .map(|nb| (nb, XrunReason::LateFinish))
+                })
+                .or_else(|| {
+                    nodes()
+                        .max_by_key(busy)
+                        .map(|nb| (nb, XrunReason::LongestBusy))
+                })
+                .map(|(nb, reason)| (nb.clone(), reason));
+
+            Some(Self {
+                sample,
+                culprit,
+                before: prev.clone(),
+                cycle: p.clone(),
+                after: None,
+            })
+        }
+    }
+
+    const MAX_XRUNS: usize = 500;
+
+    pub struct Driver {
+        last_profiling: Option<Profiling>,
+        history: RingBuf<Profiling>,
+
+        measurements: DriverMeasurements,
+        followers: BTreeMap<i32, Client>,
+        // Calculated when first needed after new measurements arrive or the window changes,
+        // so that only the statistics that are shown are calculated
+        stats: OnceCell<DriverStats>,
+        stats_window: Window,
+        // Lets what's derived from all of the measurements be cached until they change
+        generation: usize,
+
+        // Total number of profilings added, used to locate xruns in the measurements
+        samples: usize,
+        xruns: RingBuf<Xrun>,
+
+        // Stored weakly as these objects live for as long as there
+        // are stored profilings of them, which can be longer than
+        // the lifetime of the global
+        pub global: Weak<RefCell<Global>>,
+    }
+
+    impl Driver {
+        pub fn with_max_profilings(max_profilings: usize, global: Weak<RefCell<Global>>) -> Self {
+            Self {
+                last_profiling: None,
+                history: RingBuf::new(),
+
+                measurements: DriverMeasurements::with_max_profilings(max_profilings),
+                followers: BTreeMap::new(),
+                stats: OnceCell::new(),
+                stats_window: Window::All,
+                generation: 0,
+
+                samples: 0,
+                xruns: RingBuf::new(),
+
+                global,
+            }
+        }
+
+        pub fn add_profiling(
+            &mut self,
+            profiling: Profiling,
+            max_profilings: usize,
+            max_history: usize,
+            global_getter: &impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+        ) {
+            if let Some(xrun) = self.xruns.back_mut()
+                && xrun.after.is_none()
+                && xrun.sample + 1 == self.samples
+            {
+                xrun.after = Some(profiling.clone());
+            }
+
+            if let Some(xrun) = self
+                .last_profiling
+                .as_ref()
+                .and_then(|prev| Xrun::detect(prev, &profiling, self.samples))
+            {
+                self.xruns.push_back(MAX_XRUNS, xrun);
+            }
+
+            self.samples += 1;
+
+            // Followers that appear now will be padded to this
+            let offset = self.measurements.len();
+
+            self.measurements.push(max_profilings, &profiling);
+
+            // Add measurements to registered followers and delete those that have no non-empty measurements
+            self.followers.retain(|id, follower| {
+                if let Some(f) = profiling.followers.iter().find(|nb| nb.id == *id) {
+                    follower.add_measurement(
+                        f,
+                        &profiling.driver,
+                        profiling.clock.rate.denom,
+                        max_profilings,
+                    );
+                } else {
+                    follower.add_empty_measurement(max_profilings);
+                }
+
+                !follower.is_empty()
+            });
+
+            // Add new followers or update their referenced globals (PipeWire reuses IDs for globals)
+            for follower in &profiling.followers {
+                match self.followers.entry(follower.id) {
+                    Entry::Occupied(mut e) => {
+                        let client = e.get_mut();
+
+                        if client.global.upgrade().is_none()
+                            && let Some(global) = global_getter(follower.id)
+                        {
+                            client.global = global;
+                            client.name.clone_from(&follower.name);
+                            client.title = format!("{}/{}", follower.name, follower.id);
+                        }
+                    }
+                    Entry::Vacant(e) => {
+                        if let Some(global) = global_getter(follower.id) {
+                            e.insert(Client::new(follower, max_profilings, offset, global))
+                                .add_measurement(
+                                    follower,
+                                    &profiling.driver,
+                                    profiling.clock.rate.denom,
+                                    max_profilings,
+                                );
+                        }
+                    }
+                }
+            }
+
+            if max_history > 0 {
+                self.history.push_back(max_history, profiling.clone());
+            } else {
+                self.history.clear();
+            }
+
+            self.last_profiling = Some(profiling);
+
+            self.measurements_changed();
+        }
+
+        pub const fn last_profiling(&self) -> Option<&Profiling> {
+            self.last_profiling.as_ref()
+        }
+
+        pub fn name(&self) -> Option<&str> {
+            self.last_profiling().map(|p| p.driver.name.as_str())
+        }
+
+        pub fn clear(&mut self) {
+            self.measurements.clear();
+            self.followers.clear();
+            self.xruns.clear();
+            self.history.clear();
+            self.measurements_changed();
+        }
+
+        fn measurements_changed(&mut self) {
+            self.generation += 1;
+            self.outdate_stats();
+        }
+
+        /// Changes whenever the retained measurements change
+        pub const fn generation(&self) -> usize {
+            self.generation
+        }
+
+        fn outdate_stats(&mut self) {
+            self.stats.take();
+            for follower in self.followers.values_mut() {
+                follower.stats.take();
+            }
+        }
+
+        pub fn set_stats_window(&mut self, window: Window) {
+            if self.stats_window != window {
+                self.stats_window = window;
+                self.outdate_stats();
+            }
+        }
+
+        pub fn stats(&self) -> &DriverStats {
+            self.stats.get_or_init(|| {
+                self.measurements
+                    .stats(self.measurements.window_len(self.stats_window))
+            })
+        }
+
+        pub fn client_stats<'a>(&self, client: &'a Client) -> &'a NodeStats {
+            client.stats.get_or_init(|| {
+                let m = &client.measurements;
+                NodeStats::calculate(
+                    &m.end_date,
+                    &m.scheduling_latency,
+                    &m.duration,
+                    self.measurements.window_len(self.stats_window),
+                )
+            })
+        }
+
+        pub fn adjust_queues(&mut self, max_profilings: usize, max_history: usize) {
+            let len = self.len();
+
+            self.history.resize(max_history);
+            self.measurements.adjust_queues(max_profilings);
+            for follower in self.followers.values_mut() {
+                follower.measurements.adjust_queues(max_profilings);
+            }
+
+            if self.len() != len {
+                self.measurements_changed();
+            }
+        }
+
+        pub fn end_date(&self, positions: &Positions, span: Span) -> PlotPoints<'static> {
+            column_plot_points(positions, span, &self.measurements.end_date)
+        }
+
+        pub fn node_values(&self, metric: NodeMetric) -> impl Iterator<Item = f64> {
+            self.measurements.node_values(metric)
+        }
+
+        pub fn clients(&self) -> impl Iterator<Item = &Client> {
+            self.followers.values()
+        }
+
+        pub fn client(&self, id: i32) -> Option<&Client> {
+            self.followers.get(&id)
+        }
+
+        pub fn xruns(&self) -> impl Iterator<Item = &Xrun> {
+            self.xruns.iter()
+        }
+
+        pub fn positions(&self, axis: XAxis) -> Positions<'_> {
+            self.measurements.positions(axis, None)
+        }
+
+        /// Positions with the time measured from `origin` instead of the first sample,
+        /// so that the samples of different drivers line up
+        pub fn positions_since(&self, axis: XAxis, origin: i64) -> Positions<'_> {
+            self.measurements.positions(axis, Some(origin))
+        }
+
+        pub fn client_load(
+            &self,
+            client: &Client,
+            metric: NodeMetric,
+        ) -> impl Iterator<Item = f64> {
+            client
+                .values(metric)
+                .zip(self.measurements.quantum.iter())
+                .map(|(v, &quantum)| v / quantum)
+        }
+
+        pub fn len(&self) -> usize {
+            self.measurements.len()
+        }
+
+        /// Index of the first retained sample counting all samples ever added
+        pub fn first_sample(&self) -> usize {
+            self.samples - self.measurements.len()
+        }
+
+        /// Index of the first sample kept in full counting all samples ever added
+        pub fn first_full_sample(&self) -> usize {
+            self.samples - self.history.len()
+        }
+
+        /// `sample` counts all samples ever added
+        pub fn profiling(&self, sample: usize) -> Option<&Profiling> {
+            self.history
+                .get(sample.checked_sub(self.first_full_sample())?)
+        }
+
+        pub fn cycle(&self, sample: usize) -> Option<CycleSpans<'_>> {
+            let m = &self.measurements;
+
+            let driver = NodeSpan::from_measurements(
+                self.name().unwrap_or("Driver"),
+                *m.end_date.get(sample)?,
+                *m.scheduling_latency.get(sample)?,
+                *m.duration.get(sample)?,
+            )?;
+
+            let first = m.nsec.get(0).copied().unwrap_or_default();
+
+            Some(CycleSpans {
+                quantum: *m.quantum.get(sample)?,
+                time: (*m.nsec.get(sample)? - first) as f64 / 1_000_000_000.,
+                nodes: std::iter::once(driver)
+                    .chain(
+                        self.followers
+                            .values()
+                            .filter_map(|c| c.measurements.span(c.title(), sample)),
+                    )
+                    .collect(),
+            })
+        }
+
+        pub fn first_nsec(&self) -> Option<i64> {
+            self.measurements.nsec.iter().next().copied()
+        }
+
+        pub fn metric(
+            &self,
+            metric: DriverMetric,
+            normalization: Normalization,
+            positions: &Positions,
+            span: Span,
+        ) -> PlotPoints<'static> {
+            self.measurements
+                .plot_points(metric, normalization, positions, span)
+        }
+
+        pub fn xrun_positions(&self) -> impl Iterator<Item = usize> {
+            let first = self.samples - self.measurements.len();
+
+            self.xruns
+                .iter()
+                .filter(move |xrun| xrun.sample >= first)
+                .map(move |xrun| xrun.sample - first)
+        }
+
+        pub fn xrun_position(&self, xrun: &Xrun) -> Option<usize> {
+            xrun.sample
+                .checked_sub(self.samples - self.measurements.len())
+        }
+
+        pub fn n_clients(&self) -> usize {
+            self.followers.len()
+        }
+    }
+}
+
+use data::{
+    Client, Driver, DriverMetric, NodeMetric, NodeStats, Normalization, Positions, Span, XAxis,
+    Xrun,
+};
+use histogram::Buckets;
+use stats::Window;
+
+#[allow(clippy::cast_precision_loss)]
+pub fn format_to_time(nanos: i64) -> String {
+    let nanos = nanos as f64;
+    if nanos < 1_000_000. {
+        format!("{:.3}us", nanos / 1000.)
+    } else if nanos < 1_000_000_000. {
+        format!("{:.4}ms", nanos / 1_000_000.)
+    } else {
+        format!("{:.6}s", nanos / 1_000_000_000.)
+    }
+}
+
+fn stats_window_selector(ui: &mut egui::Ui, window: &mut Window) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_label("Statistics window")
+            .selected_text(window.as_str())
+            .show_ui(ui, |ui| {
+                for w in [Window::All, Window::Cycles(1000), Window::Seconds(10.)] {
+                    let selected = std::mem::discriminant(window) == std::mem::discriminant(&w);
+                    if ui.selectable_label(selected, w.as_str()).clicked() && !selected {
+                        *window = w;
+                    }
+                }
+            });
+
+        match window {
+            Window::All => {}
+            Window::Cycles(n) => {
+                ui.add(
+                    egui::DragValue::new(n)
+                        .range(1..=1_000_000)
+                        .suffix(" cycles"),
+                );
+            }
+            Window::Seconds(secs) => {
+                ui.add(
+                    egui::DragValue::new(secs)
+                        .range(0.001..=86_400.)
+                        .speed(0.1)
+                        .suffix("s"),
+                );
+            }
+        }
+    });
+}
+
+/// Samples that the plot shows and the number of points it has room for.
+/// The bounds are the ones of the previous frame, so samples up to a plot's
+/// width away are included to have them when the plot is dragged or zoomed out.
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn plot_span(plot_ui: &egui_plot::PlotUi, positions: &Positions) -> Span {
+    let columns = plot_ui.transform().frame().width().max(1.) as usize;
+
+    if plot_ui.auto_bounds().x {
+        return positions.full_span(columns);
+    }
+
+    let bounds = plot_ui.plot_bounds();
+    let width = bounds.width();
+
+    positions.span(
+        bounds.min()[0] - width,
+        bounds.max()[0] + width,
+        columns * 3,
+    )
+}
+
+fn xrun_markers(plot_ui: &mut egui_plot::PlotUi, driver: &Driver, positions: &Positions) {
+    for sample in driver.xrun_positions() {
+        plot_ui.vline(egui_plot::VLine::new("Xrun", positions.x(sample)).color(egui::Color32::RED));
+    }
+}
+
+fn clicked_sample<R>(res: &egui_plot::PlotResponse<R>, positions: &Positions) -> Option<usize> {
+    if !res.response.clicked() {
+        return None;
+    }
+
+    let pos = res.response.interact_pointer_pos()?;
+
+    positions
+        .nearest(res.transform.value_from_position(pos).x)
+        .map(|(sample, _)| sample)
+}
+
+const fn transport_state_str(state: i32) -> &'static str {
+    // spa_io_position_state
+    match state {
+        0 => "Stopped",
+        1 => "Starting",
+        2 => "Running",
+        _ => "Unknown",
+    }
+}
+
+const fn node_status_str(status: i32) -> &'static str {
+    // PW_NODE_ACTIVATION_*
+    match status {
+        0 => "Not triggered",
+        1 => "Triggered",
+        2 => "Awake",
+        3 => "Finished",
+        4 => "Inactive",
+        _ => "Unknown",
+    }
+}
+
+fn draw_profiling(ui: &mut egui::Ui, driver: &Driver, p: &Profiling, sx: &backend::Sender) {
+    use crate::ui::util::uis::key_val_display;
+
+    fn optional<T: ToString>(value: Option<T>) -> String {
+        value.map_or_else(|| "Not reported".to_owned(), |v| v.to_string())
+    }
+
+    let info = &p.info;
+    key_val_display(
+        ui,
+        400.,
+        f32::INFINITY,
+        "Info",
+        [
+            ("Counter", info.counter.to_string()),
+            ("CPU Load Fast", info.cpu_load_fast.to_string()),
+            ("CPU Load Medium", info.cpu_load_medium.to_string()),
+            ("CPU Load Slow", info.cpu_load_slow.to_string()),
+            ("Xruns", info.xrun_count.to_string()),
+        ]
+        .into_iter(),
+    );
+
+    let clock = &p.clock;
+    key_val_display(
+        ui,
+        400.,
+        f32::INFINITY,
+        "Clock",
+        [
+            ("Flags", format!("{:#x}", clock.flags)),
+            ("ID", clock.id.to_string()),
+            ("Name", clock.name.clone()),
+            ("Time", format!("{} ns", clock.nsec)),
+            ("Rate", format!("{}/{}", clock.rate.num, clock.rate.denom)),
+            ("Position", clock.position.to_string()),
+            ("Duration", clock.duration.to_string()),
+            ("Delay", clock.delay.to_string()),
+            ("Rate Difference", format!("{:.9}", clock.rate_diff)),
+            (
+                "Next Time",
+                format!(
+                    "{} ns (in {})",
+                    clock.next_nsec,
+                    format_to_time(clock.next_nsec - clock.nsec)
+                ),
+            ),
+            (
+                "Transport State",
+                optional(clock.transport_state.map(transport_state_str)),
+            ),
+            ("Cycle", optional(clock.cycle)),
+            (
+                "Xrun Duration",
+                optional(clock.xrun_duration.map(format_to_time)),
+            ),
+        ]
+        .into_iter(),
+    );
+
+    egui::CollapsingHeader::new(format!("Nodes ({})", 1 + p.followers.len()))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                egui::Grid::new("node_blocks")
+                    .striped(true)
+                    .num_columns(12)
+                    .show(ui, |ui| {
+                        for header in [
+                            "",
+                            "ID",
+                            "Name",
+                            "Status",
+                            "Latency",
+                            "Prev Signal",
+                            "Signal",
+                            "Awake",
+                            "Finish",
+                            "Waiting",
+                            "Busy",
+                            "Xruns",
+                        ] {
+                            ui.label(header);
+                        }
+                        ui.end_row();
+
+                        for (nb, is_driver) in std::iter::once((&p.driver, true))
+                            .chain(p.followers.iter().map(|nb| (nb, false)))
+                        {
+                            let global = if is_driver {
+                                driver.global.upgrade()
+                            } else {
+                                driver.client(nb.id).and_then(|c| c.global.upgrade())
+                            };
+                            global_info_button(ui, global.as_ref(), sx);
+
+                            ui.label(nb.id.to_string());
+                            ui.label(if is_driver {
+                                format!("{} (Driver)", nb.name)
+                            } else {
+                                nb.name.clone()
+                            });
+                            ui.label(node_status_str(nb.status))
+                                .on_hover_text(nb.status.to_string());
+                            ui.label(format!("{}/{}", nb.latency.num, nb.latency.denom));
+
+                            for time in [nb.prev_signal, nb.signal, nb.awake, nb.finish] {
+                                ui.label(time.to_string());
+                            }
+
+                            ui.label(format_to_time(nb.awake - nb.signal));
+                            ui.label(format_to_time(nb.finish - nb.awake));
+                            ui.label(optional(nb.xrun_count));
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+}
+
+fn show_waterfall(
+    ui: &mut egui::Ui,
+    driver_id: i32,
+    driver: &Driver,
+    selected: &mut Option<(i32, usize)>,
+) {
+    use egui_plot::{Bar, BarChart};
+
+    let len = driver.len();
+    if len == 0 {
+        ui.label("No samples");
+        return;
+    }
+
+    let first = driver.first_sample();
+    let last = first + len - 1;
+
+    // Selected cycle is of another driver or no longer retained
+    if selected.is_some_and(|(id, sample)| id != driver_id || sample < first || sample > last) {
+        *selected = None;
+    }
+
+    let mut sample = selected.map_or(last, |(_, sample)| sample);
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(sample > first, egui::Button::new("⏴ Previous"))
+            .clicked()
+        {
+            sample -= 1;
+            *selected = Some((driver_id, sample));
+        }
+
+        if ui
+            .add_enabled(sample < last, egui::Button::new("Next ⏵"))
+            .clicked()
+        {
+            sample += 1;
+            *selected = Some((driver_id, sample));
+        }
+
+        let mut index = sample - first;
+        if ui
+            .add(
+                egui::DragValue::new(&mut index)
+                    .range(0..=len - 1)
+                    .prefix("Cycle "),
+            )
+            .changed()
+        {
+            sample = first + index;
+            *selected = Some((driver_id, sample));
+        }
+
+        if ui
+            .selectable_label(selected.is_none(), "Follow latest")
+            .clicked()
+        {
+            sample = last;
+            *selected = None;
+        }
+    });
+
+    let Some(cycle) = driver.cycle(sample - first) else {
+        return;
+    };
+
+    ui.label(format!(
+        "Time: {:.6}s | Quantum: {:.3}us",
+        cycle.time, cycle.quantum
+    ));
+
+    let n = cycle.nodes.len();
+
+    let mut waiting = Vec::with_capacity(n);
+    let mut busy = Vec::with_capacity(n);
+    let mut y_labels = vec![String::new(); n];
+
+    for (i, node) in cycle.nodes.iter().enumerate() {
+        // The driver is at the top
+        let row = n - 1 - i;
+
+        waiting.push(
+            Bar::new(row as f64, node.awake - node.signal)
+                .base_offset(node.signal)
+                .name(node.name),
+        );
+
+        if node.finish >= node.awake {
+            busy.push(
+                Bar::new(row as f64, node.finish - node.awake)
+                    .base_offset(node.awake)
+                    .name(node.name),
+            );
+            y_labels[row] = node.name.to_owned();
+        } else {
+            y_labels[row] = format!("{} (not completed)", node.name);
+        }
+    }
+
+    Plot::new("waterfall")
+        .height(f32::max((n * 30) as f32, 120.))
+        .legend(egui_plot::Legend::default())
+        .allow_zoom(egui::emath::Vec2b::new(true, false))
+        .allow_drag(egui::emath::Vec2b::new(true, false))
+        .show_grid(egui::Vec2b::new(true, false))
+        .include_x(0.)
+        .include_x(cycle.quantum)
+        .include_y(-0.5)
+        .include_y(n as f64 - 0.5)
+        .y_grid_spacer(move |_| {
+            // Always show all labels
+            (0..n)
+                .map(|i| egui_plot::GridMark {
+                    step_size: n as f64,
+                    value: i as f64,
+                })
+                .collect()
+        })
+        .x_axis_formatter(|x, _| format!("{}us", x.value))
+        .y_axis_formatter(move |y, _| {
+            if y.value.is_sign_positive() && (y.value as usize) < n && y.value % 1. == 0. {
+                y_labels[y.value as usize].clone()
+            } else {
+                String::new()
+            }
+        })
+        .label_formatter(|_, p| format!("{:.3}us", p.x))
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(
+                BarChart::new("Waiting", waiting)
+                    .width(0.6)
+                    .horizontal()
+                    .element_formatter(Box::new(|b, _| {
+                        format!("{} waited {:.3}us", b.name, b.value)
+                    })),
+            );
+            plot_ui.bar_chart(
+                BarChart::new("Busy", busy)
+                    .width(0.6)
+                    .horizontal()
+                    .element_formatter(Box::new(|b, _| {
+                        format!("{} was busy for {:.3}us", b.name, b.value)
+                    })),
+            );
+            plot_ui.vline(
+                egui_plot::VLine::new("Quantum deadline", cycle.quantum).color(egui::Color32::RED),
+            );
+        });
+}
+
+fn load_color(load: f64) -> egui::Color32 {
+    let load = load.clamp(0., 1.) as f32;
+
+    if load < 0.5 {
+        egui::Color32::from_rgb((load * 2. * 255.) as u8, 200, 0)
+    } else {
+        egui::Color32::from_rgb(255, ((1. - load) * 2. * 200.) as u8, 0)
+    }
+}
+
+/// Keeps what's calculated from all of the measurements of a driver between frames
+/// and calculates it again only when the key changes
+fn cached<K, T>(ui: &egui::Ui, id: egui::Id, key: K, calculate: impl FnOnce() -> T) -> T
+where
+    K: Clone + PartialEq + Send + Sync + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    if let Some((cached_key, value)) = ui.data(|d| d.get_temp::<(K, T)>(id))
+        && cached_key == key
+    {
+        return value;
+    }
+
+    let value = calculate();
+    ui.data_mut(|d| d.insert_temp(id, (key, value.clone())));
+
+    value
+}
+
+/// Every column of the heatmap is the maximum of the samples that fall into it so spikes aren't hidden
+fn show_heatmap(
+    ui: &mut egui::Ui,
+    id: i32,
+    driver: &Driver,
+    positions: &Positions,
+    metric: NodeMetric,
+) {
+    const ROW_HEIGHT: f32 = 16.;
+    const LABEL_WIDTH: f32 = 200.;
+
+    let len = driver.len();
+
+    let mut clients: Vec<&Client> = driver.clients().collect();
+    clients.sort_unstable_by(|a, b| a.title().cmp(b.title()));
+
+    if len == 0 || clients.is_empty() {
+        ui.label("No followers");
+        return;
+    }
+
+    let (min_x, max_x) = (positions.x(0), positions.max_x());
+    let width = (ui.available_width() - LABEL_WIDTH).max(1.);
+    let columns = len.min(width as usize).max(1);
+
+    let column_of = |sample: usize| {
+        if max_x > min_x {
+            (((positions.x(sample) - min_x) / (max_x - min_x) * columns as f64) as usize)
+                .min(columns - 1)
+        } else {
+            0
+        }
+    };
+
+    // Maximum load and sample range of each cell
+    let key = (driver.generation(), columns, metric, positions.axis());
+    let cells = cached(ui, egui::Id::new(("heatmap", id)), key, || {
+        let mut cells = vec![(f64::NAN, usize::MAX, 0); clients.len() * columns];
+        for (row, client) in clients.iter().enumerate() {
+            for (sample, load) in driver.client_load(client, metric).enumerate() {
+                let cell = &mut cells[row * columns + column_of(sample)];
+
+                // NaNs are ignored
+                cell.0 = cell.0.max(load);
+                cell.1 = cell.1.min(sample);
+                cell.2 = cell.2.max(sample);
+            }
+        }
+        cells
+    });
+
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), ROW_HEIGHT * clients.len() as f32),
+        egui::Sense::hover(),
+    );
+
+    let painter = ui.painter_at(rect);
+    let label_painter = painter.with_clip_rect(egui::Rect::from_min_size(
+        rect.min,
+        egui::vec2(LABEL_WIDTH - 4., rect.height()),
+    ));
+    let column_width = width / columns as f32;
+    let empty = ui.visuals().extreme_bg_color;
+
+    for (row, client) in clients.iter().enumerate() {
+        let y = (row as f32).mul_add(ROW_HEIGHT, rect.top());
+
+        label_painter.text(
+            egui::pos2(rect.left(), y + ROW_HEIGHT / 2.),
+            egui::Align2::LEFT_CENTER,
+            client.title(),
+            egui::FontId::proportional(ROW_HEIGHT * 0.75),
+            ui.visuals().text_color(),
+        );
+
+        for column in 0..columns {
+            let load = cells[row * columns + column].0;
+
+            painter.rect_filled(
+                egui::Rect::from_min_size(
+                    egui::pos2(
+                        (column as f32).mul_add(column_width, rect.left() + LABEL_WIDTH),
+                        y,
+                    ),
+                    egui::vec2(column_width, ROW_HEIGHT - 1.),
+                ),
+                0.,
+                if load.is_nan() {
+                    empty
+                } else {
+                    load_color(load)
+                },
+            );
+        }
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let row = ((pointer.y - rect.top()) / ROW_HEIGHT) as usize;
+        let column = ((pointer.x - rect.left() - LABEL_WIDTH) / column_width) as usize;
+
+        if pointer.x >= rect.left() + LABEL_WIDTH && row < clients.len() && column < columns {
+            let (load, first, last) = cells[row * columns + column];
+
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.label(clients[row].title());
+                if first <= last {
+                    ui.label(format!("Cycles {first} - {last}"));
+                }
+                if load.is_nan() {
+                    ui.label("Not participating");
+                } else {
+                    ui.label(format!(
+                        "Max {}/Quantum: {load:.3}",
+                        match metric {
+                            NodeMetric::Duration => "Busy",
+                            NodeMetric::EndDate => "End Date",
+                            NodeMetric::SchedulingLatency => "Scheduling Latency",
+                        }
+                    ));
+                }
+            });
+        }
+    }
+}
+
+/// Drivers imported from logs use negative IDs so they never collide with PipeWire's
+const fn is_offline(driver_id: i32) -> bool {
+    driver_id < 0
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PlotMode {
+    TimeSeries,
+    Histogram,
+    Heatmap,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ProcessColumn {
+    Name,
+    Waiting,
+    Busy,
+    BusyQuantum,
+    Xruns,
+}
+
+impl ProcessColumn {
+    const fn descending_first(self) -> bool {
+        !matches!(self, Self::Name)
+    }
+}
+
+pub struct NodeLoad {
+    /// Nanoseconds the node was busy processing in the cycle
+    pub busy: i64,
+    pub busy_quantum: f64,
+    /// The xruns the node itself counted, if its version of PipeWire reports them.
+    /// The global count of the driver is left out so it isn't counted for every follower.
+    pub xruns: Option<i32>,
+}
+
+struct ProcessRow<'a> {
+    block: &'a NodeBlock,
+    profiling: &'a Profiling,
+    is_driver: bool,
+    driver: &'a Driver,
+    client: Option<&'a Client>,
+    global: Option<Rc<RefCell<Global>>>,
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl<'a> ProcessRow<'a> {
+    fn of_driver(driver: &'a Driver) -> Vec<Self> {
+        let Some(profiling) = driver.last_profiling() else {
+            return Vec::new();
+        };
+
+        std::iter::once(Self {
+            block: &profiling.driver,
+            profiling,
+            is_driver: true,
+            driver,
+            client: None,
+            global: driver.global.upgrade(),
+        })
+        .chain(driver.clients().filter_map(|client| {
+            client.last_profiling().map(|block| Self {
+                block,
+                profiling,
+                is_driver: false,
+                driver,
+                client: Some(client),
+                global: client.global.upgrade(),
+            })
+        }))
+        .collect()
+    }
+
+    const fn waiting(&self) -> i64 {
+        self.block.awake - self.block.signal
+    }
+
+    /// Calculated only for the rows that are shown
+    fn stats(&self) -> &'a NodeStats {
+        match self.client {
+            Some(client) => self.driver.client_stats(client),
+            None => &self.driver.stats().node,
+        }
+    }
+
+    const fn busy(&self) -> i64 {
+        self.block.finish - self.block.awake
+    }
+
+    fn quantum(&self) -> f64 {
+        let clock = &self.profiling.clock;
+        clock.duration as f64 * f64::from(clock.rate.num) / f64::from(clock.rate.denom)
+    }
+
+    fn busy_quantum(&self) -> f64 {
+        self.busy() as f64 / 1_000_000_000. / self.quantum()
+    }
+
+    fn xruns(&self) -> i32 {
+        self.block
+            .xrun_count
+            .unwrap_or(self.profiling.info.xrun_count)
+    }
+
+    fn matches(&self, filter: &KvMatcher) -> bool {
+        match &self.global {
+            Some(global) => filter.matches(&global.borrow().props().iter()),
+            None => filter.matches(&std::iter::empty::<(&str, &str)>()),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: ProcessColumn) -> std::cmp::Ordering {
+        match column {
+            ProcessColumn::Name => self.block.name.cmp(&other.block.name),
+            ProcessColumn::Waiting => self.waiting().cmp(&other.waiting()),
+            ProcessColumn::Busy => self.busy().cmp(&other.busy()),
+            ProcessColumn::BusyQuantum => self.busy_quantum().total_cmp(&other.busy_quantum()),
+            ProcessColumn::Xruns => self.xruns().cmp(&other.xruns()),
+        }
+    }
+}
+
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Default)]
+pub struct PersistentData {
+    baselines: Vec<baseline::Baseline>,
+}
+
+pub struct Profiler {
+    sources: BTreeMap<u32, Weak<RefCell<Global>>>,
+    /// Kept when profiling is turned off
+    source: Option<u32>,
+    profiling: bool,
+
+    max_profilings: usize,
+    max_history: usize,
+    drivers: HashMap<i32, Driver>,
+    selected_driver_id: Option<i32>,
+    pause: bool,
+
+    stats_window: Window,
+
+    /// Driver and sample shown in the waterfall, [`None`] follows the latest sample
+    waterfall_cycle: Option<(i32, usize)>,
+    open_waterfall: bool,
+    inspected_cycle: Option<(i32, usize)>,
+
+    compared_drivers: BTreeSet<i32>,
+    comparison_metric: DriverMetric,
+    comparison_stacked: bool,
+    comparison_normalization: Normalization,
+
+    plot_mode: PlotMode,
+    x_axis: XAxis,
+    histogram_buckets: usize,
+    histogram_log: bool,
+    heatmap_metric: NodeMetric,
+
+    /// Column the Process Viewer is sorted by and whether it's descending.
+    /// [`None`] keeps the drivers first and the followers in ID order
+    process_sort: Option<(ProcessColumn, bool)>,
+    process_filter: KvMatcher,
+    process_all_drivers: bool,
+
+    trigger: trigger::Trigger,
+
+    baselines: Vec<baseline::Baseline>,
+    overlaid_baseline: Option<usize>,
+    baseline_name: String,
+
+    log_path: String,
+    log_writer: Option<log::Writer>,
+    log_status: Option<String>,
+    next_offline_driver_id: i32,
+
+    /// Temporarily holds incoming data until the update interval passes
+    buffer: RingBuf<Profiling>,
+
+    // Used for updating last profilings of nodes periodically instead of on every new profiling.
+    // This is useful for not drawing new data on every egui update, such as mouse movement
+    last_profs_update: std::time::Instant,
+    refresh_this_frame: Option<bool>,
+}
+
+impl PersistentView for Profiler {
+    type Data = PersistentData;
+
+    fn with_data(data: &Self::Data) -> Self {
+        Self {
+            baselines: data.baselines.clone(),
+            ..Self::with_max_profilings(250)
+        }
+    }
+
+    fn save_data(&self) -> Option<Self::Data> {
+        // Saved even when empty so that deleting every baseline persists
+        Some(PersistentData {
+            baselines: self
+                .baselines
+                .iter()
+                .map(baseline::Baseline::for_storage)
+                .collect(),
+        })
+    }
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+impl Profiler {
+    pub fn with_max_profilings(max_profilings: usize) -> Self {
+        Self {
+            sources: BTreeMap::new(),
+            source: None,
+            profiling: true,
+
+            max_profilings,
+            max_history: 1000,
+            drivers: HashMap::new(),
+            selected_driver_id: None,
+            pause: false,
+
+            stats_window: Window::All,
+
+            waterfall_cycle: None,
+            open_waterfall: false,
+            inspected_cycle: None,
+
+            compared_drivers: BTreeSet::new(),
+            comparison_metric: DriverMetric::Period,
+            comparison_stacked: false,
+            comparison_normalization: Normalization::None,
+
+            plot_mode: PlotMode::TimeSeries,
+            x_axis: XAxis::Cycle,
+            histogram_buckets: 50,
+            histogram_log: true,
+            heatmap_metric: NodeMetric::Duration,
+
+            process_sort: None,
+            process_filter: KvMatcher::new(),
+            process_all_drivers: false,
+
+            trigger: trigger::Trigger::new(),
+
+            baselines: Vec::new(),
+            overlaid_baseline: None,
+            baseline_name: String::from("Baseline"),
+
+            log_path: String::from("profiler.log"),
+            log_writer: None,
+            log_status: None,
+            next_offline_driver_id: -1,
+
+            buffer: RingBuf::new(),
+
+            last_profs_update: std::time::Instant::now(),
+            refresh_this_frame: None,
+        }
+    }
+
+    fn update_data(
+        &mut self,
+        update_rate: std::time::Duration,
+        global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+    ) {
+        for driver in self.drivers.values_mut() {
+            driver.set_stats_window(self.stats_window);
+        }
+
+        // No need to query the clock, refresh instantly
+        if !update_rate.is_zero() {
+            let now = std::time::Instant::now();
+
+            if now.duration_since(self.last_profs_update) >= update_rate {
+                self.last_profs_update = now;
+            } else {
+                if self.refresh_this_frame.is_none() {
+                    self.refresh_this_frame = Some(false);
+                }
+                return;
+            }
+        }
+
+        self.refresh_this_frame = Some(true);
+
+        for driver in self.drivers.values_mut() {
+            driver.adjust_queues(self.max_profilings, self.max_history);
+        }
+
+        for p in self.buffer.drain(..) {
+            match self.drivers.entry(p.driver.id) {
+                Entry::Occupied(mut e) => {
+                    e.get_mut().add_profiling(
+                        p,
+                        self.max_profilings,
+                        self.max_history,
+                        &global_getter,
+                    );
+                }
+                Entry::Vacant(e) => {
+                    if let Some(global) = global_getter(p.driver.id) {
+                        e.insert(Driver::with_max_profilings(self.max_profilings, global))
+                            .add_profiling(
+                                p,
+                                self.max_profilings,
+                                self.max_history,
+                                &global_getter,
+                            );
+                    }
+                }
+            }
+        }
+
+        // New drivers start with the default window
+        for driver in self.drivers.values_mut() {
+            driver.set_stats_window(self.stats_window);
+        }
+    }
+
+    pub fn node_loads(
+        &mut self,
+        update_rate: std::time::Duration,
+        global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+    ) -> HashMap<i32, NodeLoad> {
+        self.update_data(update_rate, global_getter);
+
+        self.drivers
+            .iter()
+            .filter(|(id, _)| !is_offline(**id))
+            .flat_map(|(_, driver)| ProcessRow::of_driver(driver))
+            .map(|row| {
+                (
+                    row.block.id,
+                    NodeLoad {
+                        busy: row.busy().max(0),
+                        busy_quantum: row.busy_quantum().max(0.),
+                        xruns: row.block.xrun_count,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Makes a Profiler global selectable as a source.
+    /// It's selected if there's no selected source.
+    pub fn add_source(&mut self, id: u32, global: &Rc<RefCell<Global>>, sx: &backend::Sender) {
+        self.sources.insert(id, Rc::downgrade(global));
+
+        if self.source.is_none() {
+            self.select_source(Some(id), sx);
+        }
+    }
+
+    pub fn remove_source(&mut self, id: u32, sx: &backend::Sender) {
+        if self.sources.remove(&id).is_some() && self.source == Some(id) {
+            self.select_source(self.sources.keys().next().copied(), sx);
+        }
+    }
+
+    fn select_source(&mut self, id: Option<u32>, sx: &backend::Sender) {
+        self.source = id;
+
+        if self.profiling {
+            sx.send(Request::SelectProfiler(id)).ok();
+        }
+    }
+
+    fn show_sources(
+        &mut self,
+        ui: &mut egui::Ui,
+        sx: &backend::Sender,
+        global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+    ) {
+        fn origin(
+            global: &Global,
+            global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+        ) -> String {
+            let props = global.props();
+
+            let owner = |key: &str, kind: &str| {
+                let id = props.get(key)?;
+                let name = id
+                    .parse()
+                    .ok()
+                    .and_then(&global_getter)
+                    .and_then(|object| object.upgrade())
+                    .and_then(|object| {
+                        let object = object.borrow();
+                        object
+                            .props()
+                            .get("application.name")
+                            .or_else(|| object.name())
+                            .cloned()
+                    });
+
+                Some(name.map_or_else(
+                    || format!("{kind} {id}"),
+                    |name| format!("{kind} {id} ({name})"),
+                ))
+            };
+
+            owner("module.id", "Module")
+                .or_else(|| owner("client.id", "Client"))
+                .unwrap_or_else(|| String::from("Remote"))
+        }
+
+        egui::CollapsingHeader::new(format!("Sources ({})", self.sources.len())).show(ui, |ui| {
+            if ui
+                .checkbox(&mut self.profiling, "Profiling")
+                .on_hover_text(
+                    "Collecting profiling data has a cost on the remote.\n\
+                    Turning this off unbinds the profiler so no data is collected.",
+                )
+                .changed()
+            {
+                sx.send(Request::SelectProfiler(
+                    self.profiling.then_some(self.source).flatten(),
+                ))
+                .ok();
+            }
+
+            if self.sources.is_empty() {
+                ui.label("No Profiler globals available");
+                return;
+            }
+
+            let mut selected = self.source;
+
+            egui::Grid::new("sources")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (id, global) in &self.sources {
+                        let global = global.upgrade();
+
+                        ui.radio_value(&mut selected, Some(*id), format!("Profiler {id}"));
+                        ui.label(global.as_ref().map_or_else(String::new, |global| {
+                            origin(&global.borrow(), &global_getter)
+                        }));
+                        global_info_button(ui, global.as_ref(), sx);
+                        ui.end_row();
+                    }
+                });
+
+            if selected != self.source {
+                self.select_source(selected, sx);
+            }
+        });
+    }
+
+    pub const fn pause(&mut self) {
+        self.pause = true;
+    }
+
+    pub fn add_profilings(&mut self, profilings: Vec<Profiling>) {
+        // Recording is independent of the pause state
+        if let Some(writer) = &mut self.log_writer
+            && let Err(e) = profilings.iter().try_for_each(|p| writer.write(p))
+        {
+            self.log_status = Some(format!("Recording stopped: {e}"));
+            self.log_writer = None;
+        }
+
+        // Triggering is also independent of the pause state
+        let captured = self.trigger.process(&profilings);
+
+        if self.pause {
+            return;
+        }
+
+        self.buffer
+            .extend(self.max_profilings, profilings.into_iter());
+
+        // Pause after adding so that the cycles after the trigger are shown
+        if captured && self.trigger.pause {
+            self.pause = true;
+        }
+    }
+
+    fn add_offline_driver(&mut self, profilings: Vec<Profiling>) {
+        let id = self.next_offline_driver_id;
+
+        let driver = self
+            .drivers
+            .entry(id)
+            .or_insert_with(|| Driver::with_max_profilings(self.max_profilings, Weak::new()));
+
+        // Offline followers have no globals
+        for p in profilings {
+            driver.add_profiling(p, self.max_profilings, self.max_history, &|_| {
+                Some(Weak::new())
+            });
+        }
+
+        self.next_offline_driver_id -= 1;
+        self.selected_driver_id = Some(id);
+    }
+
+    fn import_log(&mut self, path: &Path) {
+        let id = self.next_offline_driver_id;
+
+        match log::read(path, id) {
+            Ok(profilings) => {
+                let n = profilings.len();
+
+                self.add_offline_driver(profilings);
+
+                self.log_status = Some(format!("Imported {n} samples from {}", path.display()));
+            }
+            Err(e) => {
+                self.log_status = Some(format!("Failed to import {}: {e}", path.display()));
+            }
+        }
+    }
+
+    fn show_log_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("pw-profiler log").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                egui::TextEdit::singleline(&mut self.log_path)
+                    .hint_text("profiler.log")
+                    .desired_width(f32::INFINITY)
+                    .show(ui);
+            });
+
+            ui.horizontal(|ui| {
+                if let Some(writer) = &self.log_writer {
+                    if ui.button("Stop recording").clicked() {
+                        let writer = self.log_writer.take().unwrap();
+                        let path = writer.path().display().to_string();
+                        let samples = writer.samples();
+
+                        self.log_status = Some(match writer.finish() {
+                            Ok(()) => format!("Recorded {samples} samples to {path}"),
+                            Err(e) => format!("Failed to finish recording to {path}: {e}"),
+                        });
+                    } else {
+                        ui.label(format!(
+                            "Recording {} samples of driver {}",
+                            writer.samples(),
+                            writer
+                                .driver_id()
+                                .map_or_else(|| "(waiting)".to_owned(), |id| id.to_string())
+                        ));
+                    }
+                    return;
+                }
+
+                ui.add_enabled_ui(!self.log_path.is_empty(), |ui| {
+                    if ui
+                        .button("Record")
+                        .on_hover_text(
+                            "Record the samples of the selected driver, or of the first driver that appears \
+                            if none is selected, to a log compatible with pw-profiler.\n\
+                            gnuplot scripts are written next to the log and updated when the recording stops.",
+                        )
+                        .on_disabled_hover_text("Provide a path first")
+                        .clicked()
+                    {
+                        let driver_id = self.selected_driver_id.filter(|&id| !is_offline(id));
+
+                        match log::Writer::create(&self.log_path, driver_id) {
+                            Ok(writer) => {
+                                self.log_writer = Some(writer);
+                                self.log_status = None;
+                            }
+                            Err(e) => {
+                                self.log_status =
+                                    Some(format!("Failed to create {}: {e}", self.log_path));
+                            }
+                        }
+                    }
+
+                    if ui
+                        .button("Import")
+                        .on_hover_text("Import a pw-profiler log as an offline driver")
+                        .on_disabled_hover_text("Provide a path first")
+                        .clicked()
+                    {
+                        let path = self.log_path.clone();
+                        self.import_log(Path::new(&path));
+                    }
+                });
+            });
+
+            if let Some(status) = &self.log_status {
+                ui.label(status);
+            }
+        });
+    }
+
+    fn show_comparison(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Driver comparison").show(ui, |ui| {
+            self.compared_drivers
+                .retain(|id| self.drivers.contains_key(id));
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Drivers");
+                for (id, driver) in &self.drivers {
+                    let name = driver
+                        .name()
+                        .map_or_else(|| format!("Unnamed driver {id}"), ToOwned::to_owned);
+
+                    let mut compared = self.compared_drivers.contains(id);
+                    if ui.checkbox(&mut compared, name).changed() {
+                        if compared {
+                            self.compared_drivers.insert(*id);
+                        } else {
+                            self.compared_drivers.remove(id);
+                        }
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Measurement")
+                    .selected_text(self.comparison_metric.as_str())
+                    .show_ui(ui, |ui| {
+                        for metric in [
+                            DriverMetric::Delay,
+                            DriverMetric::Period,
+                            DriverMetric::Estimated,
+                            DriverMetric::EndDate,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.comparison_metric,
+                                metric,
+                                metric.as_str(),
+                            );
+                        }
+                    });
+
+                ui.separator();
+
+                ui.selectable_value(&mut self.comparison_stacked, false, "Overlay");
+                ui.selectable_value(&mut self.comparison_stacked, true, "Stack");
+
+                ui.separator();
+
+                ui.label("Normalize to").on_hover_text(
+                    "Quantum: Show the measurements as fractions of the quantum of each cycle.\n\
+                    The quantum is converted to time using the rate of each driver, \
+                    so drivers with different rates and quantums can be compared.\n\
+                    Rate: Show the measurements as the number of samples the clock of each driver \
+                    advances in that time, taking its rate difference into account.",
+                );
+                for normalization in [
+                    Normalization::None,
+                    Normalization::Quantum,
+                    Normalization::Rate,
+                ] {
+                    ui.selectable_value(
+                        &mut self.comparison_normalization,
+                        normalization,
+                        normalization.as_str(),
+                    );
+                }
+            });
+
+            if self.compared_drivers.is_empty() {
+                ui.label("Select drivers to compare");
+                return;
+            }
+
+            let drivers: Vec<(i32, &Driver)> = self
+                .compared_drivers
+                .iter()
+                .filter_map(|id| self.drivers.get(id).map(|d| (*id, d)))
+                .collect();
+
+            // Use the same time origin so the samples of the drivers line up.
+            // Offline drivers have their own clock, so they start from their first sample.
+            let origin = drivers
+                .iter()
+                .filter(|(id, _)| !is_offline(*id))
+                .filter_map(|(_, d)| d.first_nsec())
+                .min()
+                .unwrap_or_default();
+
+            let series: Vec<(i32, String, &Driver, Positions)> = drivers
+                .into_iter()
+                .map(|(id, driver)| {
+                    let name = driver
+                        .name()
+                        .map_or_else(|| format!("Unnamed driver {id}"), ToOwned::to_owned);
+
+                    let positions = if is_offline(id) {
+                        driver.positions(self.x_axis)
+                    } else {
+                        driver.positions_since(self.x_axis, origin)
+                    };
+
+                    (id, name, driver, positions)
+                })
+                .collect();
+
+            let normalization = self.comparison_normalization;
+            let metric = self.comparison_metric;
+            let x_axis = self.x_axis;
+
+            let plot = |id: egui::Id| {
+                Plot::new(id)
+                    .legend(egui_plot::Legend::default())
+                    .allow_zoom(egui::emath::Vec2b::new(true, false))
+                    .allow_drag(egui::emath::Vec2b::new(true, false))
+                    .link_axis(egui::Id::new("driver_comparison"), [true, false])
+                    .link_cursor(egui::Id::new("driver_comparison"), [true, false])
+                    .label_formatter(move |name, value| {
+                        if name.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{name}: {}", normalization.format(value.y))
+                        }
+                    })
+                    .x_axis_formatter(move |x, _| match x_axis {
+                        XAxis::Cycle if x.value % 1. != 0. => String::new(),
+                        XAxis::Cycle => format!("{:.0}", x.value),
+                        XAxis::Time => format!("{}s", x.value),
+                    })
+                    .y_axis_formatter(move |y, _| match normalization {
+                        Normalization::None => format!("{}us", y.value),
+                        Normalization::Quantum | Normalization::Rate => format!("{}", y.value),
+                    })
+            };
+
+            if self.comparison_stacked {
+                for (id, name, driver, positions) in &series {
+                    plot(egui::Id::new(("driver_comparison", id)))
+                        .height(150.)
+                        .show(ui, |plot_ui| {
+                            let span = plot_span(plot_ui, positions);
+                            plot_ui.line(egui_plot::Line::new(
+                                name.as_str(),
+                                driver.metric(metric, normalization, positions, span),
+                            ));
+                            xrun_markers(plot_ui, driver, positions);
+                        });
+                }
+            } else {
+                plot(egui::Id::new("driver_comparison_overlay"))
+                    .height(250.)
+                    .show(ui, |plot_ui| {
+                        for (_, name, driver, positions) in &series {
+                            let span = plot_span(plot_ui, positions);
+                            plot_ui.line(egui_plot::Line::new(
+                                name.as_str(),
+                                driver.metric(metric, normalization, positions, span),
+                            ));
+                        }
+                    });
+            }
+        });
+    }
+
+    fn show_xruns(&self, ui: &mut egui::Ui, sx: &backend::Sender) {
+        fn draw_cycles(ui: &mut egui::Ui, xrun: &Xrun) {
+            let culprit_id = xrun.culprit.as_ref().map(|(nb, _)| nb.id);
+
+            egui::Grid::new("cycles").striped(true).show(ui, |ui| {
+                for header in [
+                    "",
+                    "Period",
+                    "Driver End Date",
+                    "Delay",
+                    "Culprit Waiting",
+                    "Culprit Busy",
+                    "Xruns",
+                ] {
+                    ui.label(header);
+                }
+                ui.end_row();
+
+                for (label, p) in [
+                    ("Before", Some(&xrun.before)),
+                    ("Xrun", Some(&xrun.cycle)),
+                    ("After", xrun.after.as_ref()),
+                ] {
+                    ui.label(label);
+
+                    let Some(p) = p else {
+                        ui.label("Not received yet");
+                        ui.end_row();
+                        continue;
+                    };
+
+                    ui.label(format_to_time(p.driver.signal - p.driver.prev_signal));
+                    ui.label(format_to_time(p.driver.finish - p.driver.signal));
+                    ui.label(format_to_time(
+                        (p.clock.delay * 1_000_000_000) / i64::from(p.clock.rate.denom.max(1)),
+                    ));
+
+                    if let Some(nb) = culprit_id.and_then(|id| {
+                        std::iter::once(&p.driver)
+                            .chain(&p.followers)
+                            .find(|nb| nb.id == id)
+                    }) {
+                        ui.label(format_to_time(nb.awake - nb.signal));
+                        ui.label(format_to_time(nb.finish - nb.awake));
+                    } else {
+                        ui.label("-");
+                        ui.label("-");
+                    }
+
+                    ui.label(p.info.xrun_count.to_string());
+                    ui.end_row();
+                }
+            });
+        }
+
+        let mut xruns: Vec<_> = self
+            .drivers
+            .iter()
+            .flat_map(|(&id, driver)| driver.xruns().map(move |xrun| (id, driver, xrun)))
+            .collect();
+
+        // Most recent first
+        xruns.sort_by_key(|(_, _, xrun)| std::cmp::Reverse(xrun.cycle.clock.nsec));
+
+        egui::CollapsingHeader::new(format!("Xruns ({})", xruns.len()))
+            .id_salt("xruns")
+            .show(ui, |ui| {
+                if xruns.is_empty() {
+                    ui.label("No xruns detected");
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("xruns")
+                            .striped(true)
+                            .num_columns(6)
+                            .show(ui, |ui| {
+                                ui.label("Time")
+                                    .on_hover_text("Monotonic clock time of the cycle");
+                                ui.label("Cycle")
+                                    .on_hover_text("Position of the cycle in the plots");
+                                ui.label("Driver");
+                                ui.label("Suspected Culprit");
+                                ui.label("Reason");
+                                ui.label("");
+                                ui.end_row();
+
+                                for (id, driver, xrun) in xruns {
+                                    ui.label(format!(
+                                        "{:.6}s",
+                                        xrun.cycle.clock.nsec as f64 / 1_000_000_000.
+                                    ));
+
+                                    ui.label(driver.xrun_position(xrun).map_or_else(
+                                        || "Not retained".to_owned(),
+                                        |pos| pos.to_string(),
+                                    ));
+
+                                    ui.label(format!("{} ({id})", xrun.cycle.driver.name));
+
+                                    if let Some((nb, reason)) = &xrun.culprit {
+                                        ui.horizontal(|ui| {
+                                            let global = if nb.id == xrun.cycle.driver.id {
+                                                driver.global.upgrade()
+                                            } else {
+                                                driver
+                                                    .client(nb.id)
+                                                    .and_then(|c| c.global.upgrade())
+                                            };
+                                            global_info_button(ui, global.as_ref(), sx);
+                                            ui.label(format!("{} ({})", nb.name, nb.id));
+                                        });
+                                        ui.label(reason.as_str());
+                                    } else {
+                                        ui.label("Unknown");
+                                        ui.label("");
+                                    }
+
+                                    let res = ui.small_button("Cycles");
+                                    egui::Popup::menu(&res)
+                                        .close_behavior(
+                                            egui::PopupCloseBehavior::CloseOnClickOutside,
+                                        )
+                                        .show(|ui| draw_cycles(ui, xrun));
+
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+    }
+
+    fn show_cycle_inspector(&mut self, ctx: &egui::Context, sx: &backend::Sender) {
+        let Some((id, sample)) = self.inspected_cycle else {
+            return;
+        };
+
+        let mut open = true;
+
+        egui::Window::new("Cycle Inspector")
+            .open(&mut open)
+            .default_width(600.)
+            .show(ctx, |ui| {
+                let Some(driver) = self.drivers.get(&id) else {
+                    ui.label("The driver is no longer profiled");
+                    return;
+                };
+
+                let first = driver.first_full_sample();
+                let last = driver.first_sample() + driver.len();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(sample > first, egui::Button::new("⏴ Previous"))
+                        .clicked()
+                    {
+                        self.inspected_cycle = Some((id, sample - 1));
+                    }
+
+                    if ui
+                        .add_enabled(sample + 1 < last, egui::Button::new("Next ⏵"))
+                        .clicked()
+                    {
+                        self.inspected_cycle = Some((id, sample + 1));
+                    }
+
+                    ui.label(format!(
+                        "Cycle {} of {}",
+                        sample.saturating_sub(driver.first_sample()),
+                        driver.name().unwrap_or("Unnamed driver")
+                    ));
+                });
+
+                ui.separator();
+
+                let Some(profiling) = driver.profiling(sample) else {
+                    ui.label(
+                        "This cycle is no longer kept in full. Increase the number of full cycles to keep more.",
+                    );
+                    return;
+                };
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    draw_profiling(ui, driver, profiling, sx);
+                });
+            });
+
+        if !open {
+            self.inspected_cycle = None;
+        }
+    }
+
+    pub fn show_profiler(
+        &mut self,
+        ui: &mut egui::Ui,
+        sx: &backend::Sender,
+        update_rate: std::time::Duration,
+        global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+    ) {
+        if ui
+            .small_button("Reset")
+            .on_hover_text("Clear all profiling data")
+            .clicked()
+        {
+            self.drivers.clear();
+            self.buffer.clear();
+            self.max_profilings = 250;
+            self.selected_driver_id = None;
+            self.pause = false;
+            return;
+        }
+
+        self.show_sources(ui, sx, &global_getter);
+
+        self.show_log_controls(ui);
+
+        egui::CollapsingHeader::new("Trigger").show(ui, |ui| {
+            let drivers = self
+                .drivers
+                .iter()
+                .filter(|(id, _)| !is_offline(**id))
+                .map(|(&id, driver)| (id, driver.name().unwrap_or("Unnamed driver").to_owned()));
+
+            if let Some(cycles) = self.trigger.show(ui, drivers) {
+                self.add_offline_driver(cycles);
+            }
+        });
+
+        self.update_data(update_rate, global_getter);
+
+        self.show_xruns(ui, sx);
+
+        self.show_comparison(ui);
+
+        self.show_cycle_inspector(ui.ctx(), sx);
+
+        let Some((id, driver)) = ({
+            let driver = self
+                .selected_driver_id
+                .and_then(|id| self.drivers.get(&id).map(|d| (id, d)));
+
+            // Selected driver doesn't exist
+            if self.selected_driver_id.is_some() && driver.is_none() {
+                self.selected_driver_id = None;
+            }
+
+            let cb = egui::ComboBox::from_label("Driver");
+            if let Some(name) = driver.as_ref().map(|(_, d)| d.name()) {
+                cb.selected_text(name.unwrap_or("Unnamed driver"))
+            } else {
+                cb.selected_text("Select a driver")
+            }
+            .show_ui(ui, |ui| {
+                for (id, driver) in &self.drivers {
+                    let name = driver
+                        .name()
+                        .map_or_else(|| format!("Unnamed driver {id}"), ToOwned::to_owned);
+
+                    ui.selectable_value(&mut self.selected_driver_id, Some(*id), name);
+                }
+            });
+
+            driver
+        }) else {
+            ui.label("Select a driver to view profiling info");
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            if is_offline(id) {
+                ui.label("Offline driver imported from a log");
+            } else {
+                global_info_button(ui, driver.global.upgrade().as_ref(), sx);
+                ui.label(format!("Driver ID: {id}"));
+            }
+        });
+
+        egui::CollapsingHeader::new("Last profiling info").default_open(true).show(ui, |ui| {
+            if let Some(last) = driver.last_profiling() {
+                let info = &last.info;
+                let followers = last.followers.len();
+                ui.label(format!(
+                    "Total profiler samples: {} | Xruns: {} | Follower nodes: {}\nQuantum: {} | CPU Load: {} {} {}",
+                    info.counter, info.xrun_count, followers, last.clock.duration * i64::from(last.clock.rate.num), info.cpu_load_fast, info.cpu_load_medium, info.cpu_load_slow));
+            }
+        });
+
+        let clear = ui.horizontal(|ui| {
+            ui.label("Profilings");
+            ui.add(egui::widgets::DragValue::new(&mut self.max_profilings).range(1..=1_000_000))
+                .on_hover_text("Number of profiler samples to keep in memory. Plots only draw as many points as they have room for. Statistics, histograms and heatmaps go through all of the samples whenever new ones arrive while they're shown, so big values also slow those down.");
+
+            ui.label("Full cycles");
+            ui.add(egui::widgets::DragValue::new(&mut self.max_history).range(0..=100_000))
+                .on_hover_text("Number of the most recent samples to keep in full for the cycle inspector. Click on a plot to inspect a cycle.");
+
+            let clear = ui.button("Clear driver samples").clicked();
+
+            ui.toggle_value(&mut self.pause, "Pause");
+
+            clear
+        }).inner;
+        if clear {
+            self.drivers.get_mut(&id).unwrap().clear();
+            return;
+        }
+
+        egui::CollapsingHeader::new("Statistics").show(ui, |ui| {
+            stats_window_selector(ui, &mut self.stats_window);
+
+            egui::ScrollArea::both().max_height(300.).show(ui, |ui| {
+                egui::Grid::new("stats")
+                    .striped(true)
+                    .num_columns(11)
+                    .show(ui, |ui| {
+                        for header in [
+                            "Node", "Metric", "Samples", "Min", "Max", "Mean", "Std Dev", "P50",
+                            "P95", "P99", "P99.9",
+                        ] {
+                            ui.label(header);
+                        }
+                        ui.end_row();
+
+                        let driver_name = driver.name().unwrap_or("Driver");
+
+                        let rows = driver
+                            .stats()
+                            .iter()
+                            .map(|(metric, stats)| (driver_name, metric, stats))
+                            .chain(driver.clients().flat_map(|client| {
+                                driver
+                                    .client_stats(client)
+                                    .iter()
+                                    .map(|(metric, stats)| (client.title(), metric, stats))
+                            }));
+
+                        for (node, metric, stats) in rows {
+                            ui.label(node);
+                            ui.label(metric);
+
+                            if let Some(stats) = stats {
+                                ui.label(stats.count.to_string());
+                                for value in [
+                                    stats.min,
+                                    stats.max,
+                                    stats.mean,
+                                    stats.std_dev,
+                                    stats.p50,
+                                    stats.p95,
+                                    stats.p99,
+                                    stats.p999,
+                                ] {
+                                    ui.label(format!("{value:.3}us"));
+                                }
+                            } else {
+                                ui.label("0");
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+        egui::CollapsingHeader::new("Baseline")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut self.baseline_name);
+
+                    if ui
+                        .button("Save current window")
+                        .on_hover_text("Keep the current measurements and statistics of the driver and its followers to compare against")
+                        .clicked()
+                    {
+                        self.baselines
+                            .push(baseline::Baseline::new(self.baseline_name.clone(), driver));
+                        self.overlaid_baseline = Some(self.baselines.len() - 1);
+                    }
+                });
+
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Overlay");
+                    ui.selectable_value(&mut self.overlaid_baseline, None, "None");
+
+                    let mut remove = None;
+                    for (i, baseline) in self.baselines.iter().enumerate() {
+                        ui.selectable_value(&mut self.overlaid_baseline, Some(i), &baseline.name)
+                            .context_menu(|ui| {
+                                if ui.button("Delete").clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                    }
+
+                    if let Some(i) = remove {
+                        self.baselines.remove(i);
+                        self.overlaid_baseline = match self.overlaid_baseline {
+                            Some(o) if o == i => None,
+                            Some(o) if o > i => Some(o - 1),
+                            o => o,
+                        };
+                    }
+                })
+                .response
+                .on_hover_text("Right click on a baseline to delete it");
+
+                if let Some(baseline) = self.overlaid_baseline.and_then(|i| self.baselines.get(i)) {
+                    ui.separator();
+                    baseline.show_summary(ui, driver);
+                }
+            });
+
+        let open = std::mem::take(&mut self.open_waterfall);
+        egui::CollapsingHeader::new("Cycle waterfall")
+            .open(open.then_some(true))
+            .show(ui, |ui| {
+                show_waterfall(ui, id, driver, &mut self.waterfall_cycle);
+            })
+            .header_response
+            .on_hover_text("Click on a plot to view a cycle");
+
+        ui.horizontal(|ui| {
+            ui.label("Node plots");
+            ui.selectable_value(&mut self.plot_mode, PlotMode::TimeSeries, "Time series");
+            ui.selectable_value(&mut self.plot_mode, PlotMode::Histogram, "Histogram");
+            ui.selectable_value(&mut self.plot_mode, PlotMode::Heatmap, "Heatmap");
+
+            ui.separator();
+
+            ui.label("X axis");
+            for axis in [XAxis::Cycle, XAxis::Time] {
+                ui.selectable_value(&mut self.x_axis, axis, axis.as_str());
+            }
+
+            if self.plot_mode == PlotMode::Histogram {
+                ui.separator();
+                ui.add(
+                    egui::DragValue::new(&mut self.histogram_buckets)
+                        .range(2..=500)
+                        .suffix(" buckets"),
+                );
+                ui.checkbox(&mut self.histogram_log, "Logarithmic")
+                    .on_hover_text("Make the buckets equally sized in log scale");
+            } else if self.plot_mode == PlotMode::Heatmap {
+                ui.separator();
+                ui.selectable_value(
+                    &mut self.heatmap_metric,
+                    NodeMetric::Duration,
+                    "Busy/Quantum",
+                );
+                ui.selectable_value(
+                    &mut self.heatmap_metric,
+                    NodeMetric::EndDate,
+                    "End Date/Quantum",
+                );
+            }
+        });
+
+        if ui.input(|i| i.focused && i.key_pressed(egui::Key::Space)) {
+            self.pause = !self.pause;
+        }
+
+        if ui.ctx().will_discard() {
+            return;
+        }
+
+        fn profiler_plot<'a>(
+            ui: &mut egui::Ui,
+            heading: &str,
+            explanation: &str,
+            id: &str,
+            positions: &'a Positions,
+            max_profilings: usize,
+        ) -> Plot<'a> {
+            let reset = ui
+                .horizontal(|ui| {
+                    ui.heading(heading).on_hover_text(explanation);
+                    ui.small_button("Reset").clicked()
+                })
+                .inner;
+
+            // Separate the plot memory of each axis
+            let plot = Plot::new((id, positions.axis().as_str()))
+                .clamp_grid(true)
+                .legend(egui_plot::Legend::default())
+                .allow_zoom(egui::emath::Vec2b::new(true, false))
+                .allow_drag(egui::emath::Vec2b::new(true, false))
+                .label_formatter(move |name, value| {
+                    if name.is_empty() {
+                        String::new()
+                    } else if let Some((cycle, time)) = positions.nearest(value.x) {
+                        format!(
+                            "{name}: {:.3}us\nProcess cycle: {cycle}\nTime: {time:.6}s",
+                            value.y
+                        )
+                    } else {
+                        format!("{name}: {:.3}us", value.y)
+                    }
+                })
+                .x_axis_formatter(move |x, _| {
+                    let x = x.value;
+
+                    match positions.axis() {
+                        XAxis::Cycle => {
+                            if x.is_sign_negative() || x > max_profilings as f64 || x % 1. != 0. {
+                                String::new()
+                            } else {
+                                format!("{x:.0}")
+                            }
+                        }
+                        XAxis::Time => {
+                            if x.is_sign_negative() || x > positions.max_x() {
+                                String::new()
+                            } else {
+                                format!("{x}s")
+                            }
+                        }
+                    }
+                })
+                .y_axis_formatter(|y, _| {
+                    let y = y.value;
+                    if y.is_sign_negative() {
+                        String::new()
+                    } else {
+                        format!("{y}us")
+                    }
+                });
+
+            if reset { plot.reset() } else { plot }
+        }
+
+        fn histogram_plot(
+            ui: &mut egui::Ui,
+            id: i32,
+            driver: &Driver,
+            metric: NodeMetric,
+            buckets: usize,
+            log: bool,
+        ) {
+            use egui_plot::{Bar, BarChart};
+
+            let reset = ui
+                .horizontal(|ui| {
+                    ui.heading(format!("{} Histogram", metric.as_str()))
+                        .on_hover_text("Number of samples of the driver and each client per bucket.\nClick on the legend to show or hide nodes.");
+                    ui.small_button("Reset").clicked()
+                })
+                .inner;
+
+            // The buckets, the counts of each node and the number of non-positive values
+            let key = (driver.generation(), metric, buckets, log);
+            let histogram = cached(
+                ui,
+                egui::Id::new(("histogram", id, metric.as_str())),
+                key,
+                || {
+                    let series: Vec<(&str, Vec<f64>)> = std::iter::once((
+                        driver.name().unwrap_or("Driver"),
+                        driver.node_values(metric).collect(),
+                    ))
+                    .chain(
+                        driver
+                            .clients()
+                            .map(|client| (client.title(), client.values(metric).collect())),
+                    )
+                    .collect();
+
+                    let buckets = Buckets::spanning(
+                        series.iter().flat_map(|(_, values)| values.iter().copied()),
+                        buckets,
+                        log,
+                    )?;
+
+                    let mut non_positive = 0;
+                    let counts: Vec<(String, Vec<usize>)> = series
+                        .into_iter()
+                        .map(|(name, values)| {
+                            let (counts, skipped) = buckets.counts(values.into_iter());
+                            non_positive += skipped;
+                            (name.to_owned(), counts)
+                        })
+                        .collect();
+
+                    Some((buckets, counts, non_positive))
+                },
+            );
+
+            let Some((buckets, counts, non_positive)) = histogram else {
+                ui.label("No samples");
+                return;
+            };
+
+            let format_value =
+                move |position: f64| format_to_time((buckets.value_at(position) * 1000.) as i64);
+
+            let plot = Plot::new(("histogram", metric.as_str()))
+                .clamp_grid(true)
+                .legend(egui_plot::Legend::default())
+                .allow_zoom(egui::emath::Vec2b::new(true, false))
+                .allow_drag(egui::emath::Vec2b::new(true, false))
+                .label_formatter(move |_, value| format_value(value.x))
+                .x_axis_formatter(move |x, _| format_value(x.value))
+                .y_axis_formatter(|y, _| {
+                    let y = y.value;
+                    if y.is_sign_negative() || y % 1. != 0. {
+                        String::new()
+                    } else {
+                        format!("{y:.0}")
+                    }
+                });
+
+            let plot = if reset { plot.reset() } else { plot };
+
+            plot.show(ui, |plot_ui| {
+                for (name, counts) in counts {
+                    let bars = counts
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(_, count)| *count != 0)
+                        .map(|(i, count)| {
+                            let (start, end) = buckets.range(i);
+                            Bar::new(buckets.center(i), count as f64)
+                                .width(buckets.width())
+                                .name(format!(
+                                    "{} - {}",
+                                    format_to_time((start * 1000.) as i64),
+                                    format_to_time((end * 1000.) as i64)
+                                ))
+                        })
+                        .collect();
+
+                    let chart_name = name.clone();
+                    plot_ui.bar_chart(BarChart::new(name, bars).element_formatter(Box::new(
+                        move |bar, _| format!("{chart_name}\n{}\n{} samples", bar.name, bar.value),
+                    )));
+                }
+            });
+
+            if non_positive > 0 {
+                ui.label(format!(
+                    "{non_positive} non-positive values not shown on the logarithmic scale"
+                ));
+            }
+        }
+
+        fn baseline_line(
+            ui: &mut egui_plot::PlotUi,
+            name: &str,
+            series: &baseline::Series,
+            axis: XAxis,
+        ) {
+            ui.line(
+                egui_plot::Line::new(format!("{name} (baseline)"), series.points(axis))
+                    .color(egui::Color32::GRAY.gamma_multiply(0.6))
+                    .style(egui_plot::LineStyle::dashed_loose()),
+            );
+        }
+
+        let positions = driver.positions(self.x_axis);
+        let overlay = self.overlaid_baseline.and_then(|i| self.baselines.get(i));
+
+        // Sample that was clicked on in a plot
+        let mut clicked = None;
+
+        ui.separator();
+
+        ui.columns_const::<2, _>( |ui| {
+            let res = profiler_plot(
+                &mut ui[0],
+                "Driver Timing",
+                "Delay: Delay to device\n\
+                              Period: Time between when the previous cycle started and when the current cycle started\n\
+                              Estimated: Estimated time until the next cycle starts",
+                "driver_timing",
+                &positions,
+                self.max_profilings,
+            )
+            .height(ui[0].available_height() / 2.)
+            .show(&mut ui[0], |ui| {
+                let span = plot_span(ui, &positions);
+                for (name, metric) in [
+                    ("Driver Delay", DriverMetric::Delay),
+                    ("Period", DriverMetric::Period),
+                    ("Estimated", DriverMetric::Estimated),
+                ] {
+                    if let Some(overlay) = overlay {
+                        baseline_line(ui, name, overlay.driver_series(metric), positions.axis());
+                    }
+                    ui.line(egui_plot::Line::new(name, driver.metric(metric, Normalization::None, &positions, span)));
+                }
+                xrun_markers(ui, driver, &positions);
+            });
+            clicked = clicked.or_else(|| clicked_sample(&res, &positions));
+
+            let res = profiler_plot(
+                &mut ui[1],
+                "Driver End Date",
+                "Time between when the current cycle started and when the driver finished processing/current cycle ended",
+                "driver_end_date",
+                &positions,
+                self.max_profilings,
+            )
+            .height(ui[1].available_height() / 2.)
+            .show(&mut ui[1], |ui| {
+                let span = plot_span(ui, &positions);
+                if let Some(overlay) = overlay {
+                    baseline_line(ui, "Driver End Date", overlay.driver_series(DriverMetric::EndDate), positions.axis());
+                }
+                ui.line(egui_plot::Line::new("Driver End Date", driver.end_date(&positions, span)));
+                xrun_markers(ui, driver, &positions);
+            });
+            clicked = clicked.or_else(|| clicked_sample(&res, &positions));
+        });
+
+        ui.separator();
+
+        if self.plot_mode == PlotMode::Heatmap {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                show_heatmap(ui, id, driver, &positions, self.heatmap_metric);
+            });
+        } else if self.plot_mode == PlotMode::Histogram {
+            ui.columns_const::<3, _>(|ui| {
+                for (i, metric) in [
+                    NodeMetric::EndDate,
+                    NodeMetric::SchedulingLatency,
+                    NodeMetric::Duration,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    histogram_plot(
+                        &mut ui[i],
+                        id,
+                        driver,
+                        metric,
+                        self.histogram_buckets,
+                        self.histogram_log,
+                    );
+                }
+            });
+        } else {
+            ui.columns_const::<3, _>(|ui| {
+                for (i, (heading, explanation, id, metric, measurement)) in [
+                    (
+                        "Clients End Date",
+                        "Time between when the current cycle started and when the client finished processing",
+                        "clients_end_date",
+                        NodeMetric::EndDate,
+                        Client::end_date as fn(&Client, &Positions, Span) -> PlotPoints<'static>,
+                    ),
+                    (
+                        "Clients Scheduling Latency",
+                        "Time between when the client was ready to start processing and when it actually started processing",
+                        "clients_scheduling_latency",
+                        NodeMetric::SchedulingLatency,
+                        Client::scheduling_latency,
+                    ),
+                    ("Clients Duration", "Time between when the client started processing and when it finished and woke up the next nodes in the graph", "clients_duration", NodeMetric::Duration, Client::duration),
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    let res = profiler_plot(&mut ui[i], heading, explanation, id, &positions, self.max_profilings).show(
+                        &mut ui[i],
+                        |ui| {
+                            let span = plot_span(ui, &positions);
+                            for client in driver.clients() {
+                                if let Some(node) = overlay.and_then(|o| o.node(client.name())) {
+                                    baseline_line(ui, client.title(), node.series(metric), positions.axis());
+                                }
+                                ui.line(egui_plot::Line::new(client.title(), measurement(client, &positions, span)));
+                            }
+                            xrun_markers(ui, driver, &positions);
+                        },
+                    );
+                    clicked = clicked.or_else(|| clicked_sample(&res, &positions));
+                }
+            });
+        }
+
+        if let Some(sample) = clicked {
+            let sample = driver.first_sample() + sample;
+
+            self.waterfall_cycle = Some((id, sample));
+            self.open_waterfall = true;
+            self.inspected_cycle = Some((id, sample));
+        }
+    }
+
+    pub fn show_process_viewer(
+        &mut self,
+        ui: &mut egui::Ui,
+        sx: &backend::Sender,
+        update_rate: std::time::Duration,
+        global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+    ) {
+        if ui
+            .small_button("Reset")
+            .on_hover_text("Clear all profiling data")
+            .clicked()
+        {
+            self.drivers.clear();
+            self.selected_driver_id = None;
+            self.pause = false;
+            return;
+        }
+
+        stats_window_selector(ui, &mut self.stats_window);
+
+        self.update_data(update_rate, global_getter);
+
+        ui.separator();
+
+        fn draw_chart(driver: &Driver, refresh: bool, ui: &mut egui::Ui) {
+            use egui_plot::{Bar, BarChart};
+
+            let mut wait = Vec::with_capacity(1 + driver.n_clients());
+            let mut busy = Vec::with_capacity(1 + driver.n_clients());
+            let mut y_labels = Vec::with_capacity(1 + driver.n_clients());
+
+            for (i, nb) in driver
+                .clients()
+                .map(|f| f.last_profiling())
+                .chain(std::iter::once(
+                    driver.last_profiling().map(|lp| &lp.driver),
+                )) // NodeBlock of the driver
+                .flatten()
+                .enumerate()
+            {
+                wait.push(Bar::new(i as f64, (nb.awake - nb.signal) as f64 / 1000.).horizontal());
+                busy.push(Bar::new(i as f64, (nb.finish - nb.awake) as f64 / 1000.).horizontal());
+
+                let label = if nb.name.len() <= 15 {
+                    format!("{} ({})", &nb.name, nb.id)
+                } else {
+                    format!("{}... ({})", &nb.name[0..15], nb.id)
+                };
+
+                y_labels.push(label);
+            }
+
+            ui.set_width(ui.available_width());
+
+            // The plot is more readable when the bounds are updated less frequently
+            // Keep the max bound equal to the max x of the last 60 updates
+            let (prev_bound, mut counter) = ui
+                .data(|d| d.get_temp::<(f64, u8)>(ui.id()))
+                .unwrap_or((0., 0));
+
+            let update_bound = counter >= 60;
+
+            let res = Plot::new("Chart")
+                .height(f32::max((y_labels.len() * 45) as f32, 115.))
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+                .allow_boxed_zoom(false)
+                .allow_axis_zoom_drag(false)
+                .show_grid(egui::Vec2b::new(true, false))
+                .set_margin_fraction(egui::Vec2::ZERO)
+                .include_x(-0.5) // Left side margin
+                .include_x(if update_bound { 0. } else { prev_bound })
+                .include_y(-0.8) // 0.8 Y margin
+                .include_y(y_labels.len() as f64 - 0.2) // 0.8 Y margin
+                .y_grid_spacer({
+                    let n_labels = y_labels.len();
+                    move |_| {
+                        // Always show all labels
+                        (0..n_labels)
+                            .map(|i| egui_plot::GridMark {
+                                step_size: n_labels as f64,
+                                value: i as f64,
+                            })
+                            .collect()
+                    }
+                })
+                .x_axis_formatter(|grid_mark, _| format!("{} us", grid_mark.value))
+                .y_axis_formatter(move |grid_mark, _| {
+                    if grid_mark.value.is_sign_positive()
+                        && (grid_mark.value as usize) < y_labels.len()
+                        && grid_mark.value % 1. == 0.
+                    {
+                        y_labels[grid_mark.value as usize].clone()
+                    } else {
+                        String::new()
+                    }
+                })
+                .label_formatter(|_, p| format!("{:.0} us", p.x))
+                .legend(
+                    egui_plot::Legend::default()
+                        .position(egui_plot::Corner::LeftTop)
+                        .text_style(egui::TextStyle::Small),
+                )
+                .show(ui, |plot_ui| {
+                    let wait = BarChart::new("Waiting", wait)
+                        .element_formatter(Box::new(|b, _| format!("Waiting took {} us", b.value)));
+
+                    let busy = BarChart::new("Busy", busy)
+                        .stack_on(&[&wait])
+                        .element_formatter(Box::new(|b, _| {
+                            format!("Processing took {} us", b.value)
+                        }));
+
+                    plot_ui.bar_chart(wait);
+                    plot_ui.bar_chart(busy);
+                });
+
+            let plot_bound = *res.transform.bounds().range_x().end();
+
+            let new_bound = if update_bound || prev_bound < plot_bound {
+                // Reset the counter if it's time to update or if the data forces the bounds to expand
+                if refresh {
+                    counter = 0;
+                }
+                plot_bound
+            } else {
+                if refresh {
+                    counter += 1;
+                }
+                prev_bound
+            };
+
+            ui.data_mut(|d| d.insert_temp(ui.id(), (new_bound, counter)));
+        }
+
+        fn sort_header(
+            ui: &mut egui::Ui,
+            sort: &mut Option<(ProcessColumn, bool)>,
+            column: ProcessColumn,
+            label: &str,
+        ) -> egui::Response {
+            let text = match *sort {
+                Some((sorted, descending)) if sorted == column => {
+                    format!("{label} {}", if descending { "⏷" } else { "⏶" })
+                }
+                _ => label.to_owned(),
+            };
+
+            let res = ui.selectable_label(sort.is_some_and(|(sorted, _)| sorted == column), text);
+
+            if res.clicked() {
+                // Cycle through the first direction, the other direction and no sorting
+                *sort = match *sort {
+                    Some((sorted, descending)) if sorted == column => {
+                        (descending == column.descending_first()).then_some((column, !descending))
+                    }
+                    _ => Some((column, column.descending_first())),
+                };
+            }
+
+            res
+        }
+
+        fn draw_header(
+            ui: &mut egui::Ui,
+            sort: &mut Option<(ProcessColumn, bool)>,
+            all_drivers: bool,
+        ) {
+            ui.label("");
+            ui.label("ID");
+            sort_header(ui, sort, ProcessColumn::Name, "Name");
+            if all_drivers {
+                ui.label("Driver");
+            }
+            ui.label("Quantum");
+            ui.label("Rate");
+            sort_header(ui, sort, ProcessColumn::Waiting, "Waiting").on_hover_text("Time between when the node was ready to start processing and when it actually started processing");
+            sort_header(ui, sort, ProcessColumn::Busy, "Busy").on_hover_text("Time between when the node started processing and when it finished and woke up the next nodes in the graph");
+            ui.label("Waiting/Quantum")
+                .on_hover_text("A measure of the graph load");
+            sort_header(ui, sort, ProcessColumn::BusyQuantum, "Busy/Quantum")
+                .on_hover_text("A measure of the load of the driver/node");
+            sort_header(ui, sort, ProcessColumn::Xruns, "Xruns");
+            ui.label("Busy P50");
+            ui.label("Busy P99");
+            ui.label("Busy Max");
+            ui.label("Waiting P99");
+            ui.end_row();
+        }
+
+        fn draw_row(row: &ProcessRow, all_drivers: bool, ui: &mut egui::Ui, sx: &backend::Sender) {
+            let block = row.block;
+            let clock = &row.profiling.clock;
+
+            global_info_button(ui, row.global.as_ref(), sx);
+
+            ui.label(block.id.to_string());
+            ui.label(&block.name);
+
+            if all_drivers {
+                ui.label(&row.profiling.driver.name);
+            }
+
+            // Quantum, Rate
+            if row.is_driver {
+                ui.label((clock.duration * i64::from(clock.rate.num)).to_string());
+                ui.label(clock.rate.denom.to_string());
+            } else {
+                for n in [block.latency.num, block.latency.denom] {
+                    if n == 0 {
+                        ui.label("Using driver's");
+                    } else {
+                        ui.label(n.to_string());
+                    }
+                }
+            }
+
+            // Waiting
+            if block.awake >= block.signal {
+                ui.label(format_to_time(row.waiting()));
+            } else if block.signal > block.prev_signal {
+                ui.label("Did not wake");
+            } else {
+                ui.label("Was not signaled");
+            }
+
+            // Busy
+            if block.finish >= block.awake {
+                ui.label(format_to_time(row.busy()));
+            } else if block.awake > block.prev_signal {
+                ui.label("Did not complete");
+            } else {
+                ui.label("Did not start");
+            }
+
+            // Waiting/Quantum, Busy/Quantum
+            ui.label(format!(
+                "{:.6}",
+                row.waiting() as f64 / 1_000_000_000. / row.quantum()
+            ));
+            ui.label(format!("{:.6}", row.busy_quantum()));
+
+            ui.label(row.xruns().to_string());
+
+            // Busy P50, P99, Max, Waiting P99
+            for value in [
+                row.stats().duration.map(|s| s.p50),
+                row.stats().duration.map(|s| s.p99),
+                row.stats().duration.map(|s| s.max),
+                row.stats().scheduling_latency.map(|s| s.p99),
+            ] {
+                if let Some(value) = value {
+                    ui.label(format_to_time((value * 1000.) as i64));
+                } else {
+                    ui.label("-");
+                }
+            }
+        }
+
+        fn arrange(
+            rows: &mut Vec<ProcessRow>,
+            filter: &KvMatcher,
+            sort: Option<(ProcessColumn, bool)>,
+        ) {
+            rows.retain(|row| row.matches(filter));
+
+            if let Some((column, descending)) = sort {
+                // Stable, so equal rows keep the drivers first
+                rows.sort_by(|a, b| {
+                    let ordering = a.compare(b, column);
+                    if descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                });
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.process_all_drivers, "All drivers")
+                .on_hover_text("Show the nodes of all drivers in one table, like pw-top");
+        });
+
+        egui::CollapsingHeader::new("Filter").show(ui, |ui| {
+            ui.label("Only show nodes whose properties match");
+            self.process_filter.show(ui);
+        });
+
+        ui.separator();
+
+        if self.process_all_drivers {
+            let mut ids: Vec<_> = self.drivers.keys().copied().collect();
+            ids.sort_unstable();
+
+            let mut rows: Vec<_> = ids
+                .iter()
+                .flat_map(|id| ProcessRow::of_driver(&self.drivers[id]))
+                .collect();
+            arrange(&mut rows, &self.process_filter, self.process_sort);
+
+            egui::ScrollArea::both().show(ui, |ui| {
+                egui::Grid::new("all_timings")
+                    .striped(true)
+                    .num_columns(15)
+                    .min_col_width(0.0)
+                    .show(ui, |ui| {
+                        draw_header(ui, &mut self.process_sort, true);
+
+                        for row in &rows {
+                            draw_row(row, true, ui, sx);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            self.refresh_this_frame = None;
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            self.drivers.retain(|id, driver| {
+                if let Some(p) = driver.last_profiling() {
+                    let mut rows = ProcessRow::of_driver(driver);
+                    arrange(&mut rows, &self.process_filter, self.process_sort);
+
+                    // Hide the drivers that don't have any matching nodes
+                    if rows.is_empty() {
+                        return true;
+                    }
+
+                    let keep = ui
+                        .horizontal(|ui| {
+                            let keep = !ui.small_button("Delete").clicked();
+                            ui.label(format!("Driver: {} (ID: {id})", &p.driver.name));
+                            keep
+                        })
+                        .inner;
+
+                    ui.push_id(id, |ui| {
+                        egui::ScrollArea::horizontal().show(ui, |ui| {
+                            egui::Grid::new("timings")
+                                .striped(true)
+                                .num_columns(14)
+                                .min_col_width(0.0)
+                                .show(ui, |ui| {
+                                    draw_header(ui, &mut self.process_sort, false);
+
+                                    for row in &rows {
+                                        draw_row(row, false, ui, sx);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    });
+
+                    if ui.ctx().will_discard() {
+                        return keep;
+                    }
+
+                    egui::CollapsingHeader::new("Chart")
+                        .id_salt(id)
+                        .show(ui, |ui| {
+                            draw_chart(driver, self.refresh_this_frame.unwrap_or(false), ui);
+                        });
+
+                    ui.separator();
+
+                    keep
+                } else {
+                    true
+                }
+            });
+        });
+
+        self.refresh_this_frame = None;
+    }
+
+    pub fn show_clock(
+        &mut self,
+        ui: &mut egui::Ui,
+        sx: &backend::Sender,
+        update_rate: std::time::Duration,
+        global_getter: impl Fn(i32) -> Option<Weak<RefCell<Global>>>,
+    ) {
+        self.update_data(update_rate, global_getter);
+
+        let mut drivers: Vec<(i32, &Driver)> =
+            self.drivers.iter().map(|(id, d)| (*id, d)).collect();
+        drivers.sort_unstable_by_key(|(id, _)| *id);
+
+        if drivers.is_empty() {
+            ui.label("No profiling data. Add a source in the Profiler view");
+            return;
+        }
+
+        // Use the same time origin so the samples of the drivers line up
+        let origin = drivers
+            .iter()
+            .filter(|(id, _)| !is_offline(*id))
+            .filter_map(|(_, d)| d.first_nsec())
+            .min()
+            .unwrap_or_default();
+
+        let series: Vec<(String, &Driver, Positions)> = drivers
+            .iter()
+            .map(|&(id, driver)| {
+                let name = driver
+                    .name()
+                    .map_or_else(|| format!("Unnamed driver {id}"), ToOwned::to_owned);
+
+                let positions = if is_offline(id) {
+                    driver.positions(XAxis::Time)
+                } else {
+                    driver.positions_since(XAxis::Time, origin)
+                };
+
+                (name, driver, positions)
+            })
+            .collect();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (metric, unit, explanation) in [
+                (
+                    DriverMetric::RateDiff,
+                    "ppm",
+                    "How much faster the clock of the driver runs than the system clock.\n\
+                    Drivers that drift apart from each other need to be resampled to stay in sync.",
+                ),
+                (DriverMetric::Delay, "us", "Delay to device"),
+                (
+                    DriverMetric::Jitter,
+                    "us",
+                    "Difference of the time between the starts of consecutive cycles from the duration of the quantum",
+                ),
+            ] {
+                ui.heading(metric.as_str()).on_hover_text(explanation);
+
+                Plot::new(("clock", metric.as_str()))
+                    .height(200.)
+                    .legend(egui_plot::Legend::default())
+                    .allow_zoom(egui::emath::Vec2b::new(true, false))
+                    .allow_drag(egui::emath::Vec2b::new(true, false))
+                    .link_axis(egui::Id::new("clock"), [true, false])
+                    .link_cursor(egui::Id::new("clock"), [true, false])
+                    .label_formatter(move |name, value| {
+                        if name.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{name}: {:.3}{unit}\nTime: {:.6}s", value.y, value.x)
+                        }
+                    })
+                    .x_axis_formatter(|x, _| format!("{}s", x.value))
+                    .y_axis_formatter(move |y, _| format!("{}{unit}", y.value))
+                    .show(ui, |plot_ui| {
+                        for (name, driver, positions) in &series {
+                            let span = plot_span(plot_ui, positions);
+                            plot_ui.line(egui_plot::Line::new(
+                                name.as_str(),
+                                driver.metric(metric, Normalization::None, positions, span),
+                            ));
+                            xrun_markers(plot_ui, driver, positions);
+                        }
+                    });
+            }
+
+            ui.separator();
+
+            ui.heading("Resampling").on_hover_text(
+                "Followers whose rate differs from the rate of their driver, so they are resampled.\n\
+                The rate of a follower is the rate of the latency it reports.\n\
+                Counted over the cycles since the follower started taking part in the driver's graph.",
+            );
+
+            egui::Grid::new("resampling")
+                .striped(true)
+                .num_columns(6)
+                .show(ui, |ui| {
+                    for header in ["Driver", "Node", "Rate", "Driver Rate", "Ratio", "Resampled"] {
+                        ui.label(header);
+                    }
+                    ui.end_row();
+
+                    for (name, driver, _) in &series {
+                        let Some(clock) = driver.last_profiling().map(|p| &p.clock) else {
+                            continue;
+                        };
+
+                        for client in driver.clients() {
+                            let resampling = client.resampling();
+                            if resampling.resampled == 0 {
+                                continue;
+                            }
+
+                            ui.label(name);
+
+                            ui.horizontal(|ui| {
+                                global_info_button(ui, client.global.upgrade().as_ref(), sx);
+                                ui.label(client.title());
+                            });
+
+                            ui.label(resampling.rate.to_string());
+                            ui.label(clock.rate.denom.to_string());
+                            ui.label(format!(
+                                "{:.6}",
+                                f64::from(resampling.rate) / f64::from(clock.rate.denom)
+                            ));
+                            ui.label(format!(
+                                "{} of {} cycles",
+                                resampling.resampled, resampling.cycles
+                            ));
+
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    }
+}
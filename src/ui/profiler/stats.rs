@@ -0,0 +1,122 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+
+/// Which of the retained samples statistics are calculated over
+#[derive(Clone, Copy, PartialEq)]
+pub enum Window {
+    All,
+    Cycles(usize),
+    Seconds(f64),
+}
+
+impl Window {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "All retained",
+            Self::Cycles(_) => "Last N cycles",
+            Self::Seconds(_) => "Last T seconds",
+        }
+    }
+}
+
+/// Summary of a series of measurements
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub p999: f64,
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Stats {
+    /// Calculates the statistics of the values, ignoring NaNs.
+    /// Returns [`None`] if there are no values.
+    pub fn calculate(values: impl Iterator<Item = f64>) -> Option<Self> {
+        let mut sorted: Vec<f64> = values.filter(|v| !v.is_nan()).collect();
+
+        if sorted.is_empty() {
+            return None;
+        }
+
+        sorted.sort_unstable_by(f64::total_cmp);
+
+        let count = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+
+        Some(Self {
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean,
+            std_dev: variance.sqrt(),
+            p50: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            p99: percentile(&sorted, 0.99),
+            p999: percentile(&sorted, 0.999),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        assert_eq!(Stats::calculate([f64::NAN].into_iter()), None);
+    }
+
+    #[test]
+    fn summary() {
+        let stats = Stats::calculate((1..=1000).map(f64::from).chain([f64::NAN])).unwrap();
+
+        assert_eq!(stats.count, 1000);
+        assert_eq!(stats.min, 1.);
+        assert_eq!(stats.max, 1000.);
+        assert_eq!(stats.mean, 500.5);
+        assert_eq!(stats.p50, 500.);
+        assert_eq!(stats.p95, 950.);
+        assert_eq!(stats.p99, 990.);
+        assert_eq!(stats.p999, 999.);
+    }
+
+    #[test]
+    fn single() {
+        let stats = Stats::calculate([4.].into_iter()).unwrap();
+
+        assert_eq!(stats.std_dev, 0.);
+        assert_eq!(stats.p50, 4.);
+        assert_eq!(stats.p999, 4.);
+    }
+}
@@ -0,0 +1,515 @@
+// Copyright 2023-2025 Dimitris Papaioannou <dimtpap@protonmail.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License version 3 as published by
+// the Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Capturing of the cycles around an event, like the trigger of an oscilloscope
+
+#![allow(clippy::cast_precision_loss)]
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    path::Path,
+};
+
+use eframe::egui;
+
+use crate::backend::pods::profiler::Profiling;
+
+use super::{data::Xrun, log};
+
+/// Measurements that can be compared to a threshold, in microseconds
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Period,
+    EndDate,
+    /// The longest time a node of the driver was busy
+    Busy,
+}
+
+impl Metric {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Period => "Period",
+            Self::EndDate => "Driver End Date",
+            Self::Busy => "Longest Busy",
+        }
+    }
+
+    fn value(self, p: &Profiling) -> f64 {
+        match self {
+            Self::Period => (p.driver.signal - p.driver.prev_signal) as f64 / 1000.,
+            Self::EndDate => (p.driver.finish - p.driver.signal) as f64 / 1000.,
+            Self::Busy => {
+                std::iter::once(&p.driver)
+                    .chain(&p.followers)
+                    .filter(|nb| nb.finish >= nb.awake)
+                    .map(|nb| nb.finish - nb.awake)
+                    .max()
+                    .unwrap_or_default() as f64
+                    / 1000.
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Condition {
+    Xrun,
+    Above { metric: Metric, threshold: f64 },
+    NodeJoined,
+    NodeLeft,
+}
+
+impl Condition {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Xrun => "Xrun",
+            Self::Above { .. } => "Metric goes above threshold",
+            Self::NodeJoined => "Node joined the driver",
+            Self::NodeLeft => "Node left the driver",
+        }
+    }
+
+    /// Why the cycle meets the condition, or [`None`] if it doesn't
+    fn check(self, prev: Option<&Profiling>, p: &Profiling) -> Option<String> {
+        // Followers of `a` that aren't followers of `b`
+        fn difference(a: &Profiling, b: &Profiling) -> Option<String> {
+            let nodes: Vec<_> = a
+                .followers
+                .iter()
+                .filter(|nb| !b.followers.iter().any(|other| other.id == nb.id))
+                .map(|nb| format!("{} ({})", nb.name, nb.id))
+                .collect();
+
+            (!nodes.is_empty()).then(|| nodes.join(", "))
+        }
+
+        match self {
+            Self::Xrun => Xrun::detect(prev?, p, 0).map(|xrun| {
+                xrun.culprit.map_or_else(
+                    || "Xrun".to_owned(),
+                    |(nb, reason)| format!("Xrun, {} ({}): {}", nb.name, nb.id, reason.as_str()),
+                )
+            }),
+            // Only when it goes above, so that staying above doesn't fire again after every capture
+            Self::Above { metric, threshold } => {
+                let value = metric.value(p);
+                (metric.value(prev?) <= threshold && value > threshold)
+                    .then(|| format!("{} was {value:.1}us", metric.as_str()))
+            }
+            Self::NodeJoined => difference(p, prev?).map(|nodes| format!("Joined: {nodes}")),
+            Self::NodeLeft => difference(prev?, p).map(|nodes| format!("Left: {nodes}")),
+        }
+    }
+}
+
+/// Cycles of a driver around the cycle that fired the trigger
+pub struct Capture {
+    pub name: String,
+    pub reason: String,
+    pub driver: String,
+    /// Index of the cycle that fired the trigger
+    pub trigger: usize,
+    pub cycles: Vec<Profiling>,
+}
+
+impl Capture {
+    /// Writes the cycles to a log compatible with `pw-profiler`
+    fn export(&self, path: &Path) -> io::Result<()> {
+        let mut writer = log::Writer::create(path, None)?;
+        for p in &self.cycles {
+            writer.write(p)?;
+        }
+        writer.finish()
+    }
+}
+
+/// A capture that is waiting for the cycles after the trigger
+struct Pending {
+    driver_id: i32,
+    remaining: usize,
+    capture: Capture,
+}
+
+pub struct Trigger {
+    condition: Condition,
+    /// Only watch this driver, or all of them if [`None`]
+    driver_id: Option<i32>,
+    before: usize,
+    after: usize,
+    armed: bool,
+    /// Disarm after capturing once
+    single: bool,
+    /// Pause the profiler after capturing
+    pub pause: bool,
+
+    /// The most recent cycles of each driver
+    recent: HashMap<i32, VecDeque<Profiling>>,
+    pending: Option<Pending>,
+    captures: Vec<Capture>,
+    captures_made: usize,
+
+    export_path: String,
+    status: Option<String>,
+}
+
+impl Trigger {
+    pub fn new() -> Self {
+        Self {
+            condition: Condition::Xrun,
+            driver_id: None,
+            before: 100,
+            after: 100,
+            armed: false,
+            single: true,
+            pause: true,
+
+            recent: HashMap::new(),
+            pending: None,
+            captures: Vec::new(),
+            captures_made: 0,
+
+            export_path: String::from("capture.log"),
+            status: None,
+        }
+    }
+
+    /// Watches the cycles for the condition.
+    /// Returns whether a capture was completed.
+    pub fn process(&mut self, profilings: &[Profiling]) -> bool {
+        if !self.armed && self.pending.is_none() {
+            self.recent.clear();
+            return false;
+        }
+
+        let mut completed = false;
+
+        for p in profilings {
+            let id = p.driver.id;
+
+            if let Some(pending) = &mut self.pending {
+                if pending.driver_id == id {
+                    pending.capture.cycles.push(p.clone());
+                    pending.remaining -= 1;
+                }
+            } else if self.armed && self.driver_id.is_none_or(|driver_id| driver_id == id) {
+                let recent = self.recent.entry(id).or_default();
+
+                if let Some(reason) = self.condition.check(recent.back(), p) {
+                    let mut cycles: Vec<_> = recent
+                        .iter()
+                        .skip(recent.len().saturating_sub(self.before))
+                        .cloned()
+                        .collect();
+                    let trigger = cycles.len();
+                    cycles.push(p.clone());
+
+                    self.captures_made += 1;
+
+                    self.pending = Some(Pending {
+                        driver_id: id,
+                        remaining: self.after,
+                        capture: Capture {
+                            name: format!("Capture {}", self.captures_made),
+                            reason,
+                            driver: format!("{} ({id})", p.driver.name),
+                            trigger,
+                            cycles,
+                        },
+                    });
+
+                    if self.single {
+                        self.armed = false;
+                    }
+                }
+            }
+
+            if self.pending.as_ref().is_some_and(|p| p.remaining == 0) {
+                self.captures.push(self.pending.take().unwrap().capture);
+                completed = true;
+            }
+
+            // At least one is needed to compare against
+            let recent = self.recent.entry(id).or_default();
+            recent.push_back(p.clone());
+            while recent.len() > self.before.max(1) {
+                recent.pop_front();
+            }
+        }
+
+        completed
+    }
+
+    /// Shows the trigger's settings and captures.
+    /// Returns the cycles of a capture that should be opened.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        drivers: impl Iterator<Item = (i32, String)>,
+    ) -> Option<Vec<Profiling>> {
+        ui.horizontal(|ui| {
+            ui.label("Condition");
+            egui::ComboBox::from_id_salt("trigger_condition")
+                .selected_text(self.condition.as_str())
+                .show_ui(ui, |ui| {
+                    for condition in [
+                        Condition::Xrun,
+                        Condition::Above {
+                            metric: Metric::EndDate,
+                            threshold: 1000.,
+                        },
+                        Condition::NodeJoined,
+                        Condition::NodeLeft,
+                    ] {
+                        let selected = std::mem::discriminant(&self.condition)
+                            == std::mem::discriminant(&condition);
+                        if ui.selectable_label(selected, condition.as_str()).clicked() && !selected
+                        {
+                            self.condition = condition;
+                        }
+                    }
+                });
+
+            if let Condition::Above { metric, threshold } = &mut self.condition {
+                egui::ComboBox::from_id_salt("trigger_metric")
+                    .selected_text(metric.as_str())
+                    .show_ui(ui, |ui| {
+                        for m in [Metric::Period, Metric::EndDate, Metric::Busy] {
+                            ui.selectable_value(metric, m, m.as_str());
+                        }
+                    });
+                ui.add(
+                    egui::DragValue::new(threshold)
+                        .range(0.0..=f64::MAX)
+                        .suffix("us"),
+                );
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Driver");
+            egui::ComboBox::from_id_salt("trigger_driver")
+                .selected_text(
+                    self.driver_id
+                        .map_or_else(|| "Any".to_owned(), |id| id.to_string()),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.driver_id, None, "Any");
+                    for (id, name) in drivers {
+                        ui.selectable_value(
+                            &mut self.driver_id,
+                            Some(id),
+                            format!("{name} ({id})"),
+                        );
+                    }
+                });
+
+            ui.label("Cycles before");
+            ui.add(egui::DragValue::new(&mut self.before).range(0..=100_000));
+            ui.label("after");
+            ui.add(egui::DragValue::new(&mut self.after).range(0..=100_000));
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.single, "Single")
+                .on_hover_text("Disarm after capturing once");
+            ui.checkbox(&mut self.pause, "Pause profiler")
+                .on_hover_text("Pause the profiler when a capture is made");
+
+            if ui.toggle_value(&mut self.armed, "Armed").changed() && !self.armed {
+                self.recent.clear();
+            }
+
+            if let Some(pending) = &self.pending {
+                ui.label(format!(
+                    "Triggered, waiting for {} more cycles",
+                    pending.remaining
+                ));
+            } else if self.armed {
+                ui.label("Waiting for the condition");
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Export path");
+            egui::TextEdit::singleline(&mut self.export_path)
+                .hint_text("capture.log")
+                .desired_width(f32::INFINITY)
+                .show(ui);
+        });
+
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+
+        let mut open = None;
+
+        egui::Grid::new("captures")
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                let mut i = 0;
+                self.captures.retain_mut(|capture| {
+                    let keep = ui
+                        .push_id(i, |ui| {
+                            let keep = !ui.small_button("Delete").clicked();
+
+                            egui::TextEdit::singleline(&mut capture.name)
+                                .desired_width(150.)
+                                .show(ui);
+
+                            ui.label(&capture.driver);
+                            ui.label(&capture.reason).on_hover_text(format!(
+                                "{} cycles before and {} after",
+                                capture.trigger,
+                                capture.cycles.len() - capture.trigger - 1
+                            ));
+
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button("Open")
+                                    .on_hover_text("Open as an offline driver")
+                                    .clicked()
+                                {
+                                    open = Some(capture.cycles.clone());
+                                }
+
+                                if ui
+                                    .add_enabled(
+                                        !self.export_path.is_empty(),
+                                        egui::Button::new("Export"),
+                                    )
+                                    .on_hover_text("Write as a pw-profiler log to the export path")
+                                    .clicked()
+                                {
+                                    let path = Path::new(&self.export_path);
+                                    self.status = Some(match capture.export(path) {
+                                        Ok(()) => format!(
+                                            "Exported {} to {}",
+                                            capture.name,
+                                            path.display()
+                                        ),
+                                        Err(e) => {
+                                            format!("Failed to export to {}: {e}", path.display())
+                                        }
+                                    });
+                                }
+                            });
+
+                            keep
+                        })
+                        .inner;
+
+                    ui.end_row();
+                    i += 1;
+
+                    keep
+                });
+            });
+
+        open
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::backend::pods::profiler::NodeBlock;
+
+    use super::*;
+
+    fn profiling(counter: i64, followers: &[i32]) -> Profiling {
+        let signal = counter * 1_000_000;
+
+        Profiling::test(
+            counter,
+            signal,
+            followers
+                .iter()
+                .map(|&id| NodeBlock::test(id, signal, signal, signal))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn captures_around_the_trigger() {
+        let mut trigger = Trigger::new();
+        trigger.condition = Condition::NodeJoined;
+        trigger.before = 2;
+        trigger.after = 1;
+        trigger.armed = true;
+
+        let cycles = [
+            profiling(0, &[]),
+            profiling(1, &[]),
+            profiling(2, &[]),
+            profiling(3, &[2]),
+        ];
+        assert!(!trigger.process(&cycles));
+        assert!(!trigger.armed);
+
+        assert!(trigger.process(&[profiling(4, &[2])]));
+
+        let capture = &trigger.captures[0];
+        assert_eq!(capture.trigger, 2);
+        assert_eq!(
+            capture
+                .cycles
+                .iter()
+                .map(|p| p.info.counter)
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn fires_when_going_above() {
+        let mut trigger = Trigger::new();
+        trigger.condition = Condition::Above {
+            metric: Metric::EndDate,
+            threshold: 500.,
+        };
+        trigger.before = 1;
+        trigger.after = 1;
+        trigger.single = false;
+        trigger.armed = true;
+
+        let above = |counter| {
+            let mut p = profiling(counter, &[]);
+            p.driver.finish = p.driver.signal + 1_000_000;
+            p
+        };
+
+        trigger.process(&[
+            profiling(0, &[]),
+            above(1),
+            above(2),
+            above(3),
+            above(4),
+            profiling(5, &[]),
+            above(6),
+            above(7),
+        ]);
+
+        assert_eq!(
+            trigger
+                .captures
+                .iter()
+                .map(|capture| capture.cycles[capture.trigger].info.counter)
+                .collect::<Vec<_>>(),
+            [1, 6]
+        );
+    }
+}
@@ -58,10 +58,18 @@ impl<T> RingBuf<T> {
         self.0.drain(range)
     }
 
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.0.get(index)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.0.iter()
     }
 
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.0.back_mut()
+    }
+
     pub fn extend(&mut self, max: usize, iter: impl ExactSizeIterator<Item = T>) {
         let mut skip = 0;
 